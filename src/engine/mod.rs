@@ -0,0 +1,358 @@
+//! A small alpha-beta search for choosing moves.
+
+use std::time::{Duration, Instant};
+
+use crate::game::{Color, Game, Move, PieceType};
+
+mod score;
+
+pub use score::Score;
+
+/// The deepest [`search_timed`] will go, however much time is left.
+const MAX_DEPTH: usize = 64;
+
+/// How many plies less deep the search after a null move goes.
+const NULL_MOVE_REDUCTION: usize = 2;
+
+/// Scores a position in centipawns from the point of view of the player
+/// to move, so positive is good for them.
+pub fn evaluate(game: &Game) -> Score {
+    let score = match game.turn() {
+        Color::White => game.material_balance(),
+        Color::Black => -game.material_balance(),
+    };
+    let mobility =
+        game.board().mobility(game.turn()) - game.board().mobility(game.turn().opposite());
+    Score::from(score + mobility + pawn_structure(game) + mop_up(game))
+}
+
+/// Penalties for doubled and isolated pawns and a bonus for passed pawns,
+/// which grows as the pawn gets closer to promoting. Scored like
+/// [`evaluate`].
+fn pawn_structure(game: &Game) -> i32 {
+    let board = game.board();
+    let side = |color: Color| {
+        let mut score = -15 * board.doubled_pawns(color) as i32;
+        score -= 10 * board.isolated_pawns(color) as i32;
+        for y in 0..8 {
+            for x in 0..8 {
+                if board.get(x, y).is_some_and(|piece| piece.color() == color)
+                    && board.is_passed_pawn(x, y)
+                {
+                    // Ranks moved up from the pawn's starting rank
+                    let advanced = match color {
+                        Color::White => 6 - y as i32,
+                        Color::Black => y as i32 - 1,
+                    };
+                    score += 20 + 10 * advanced;
+                }
+            }
+        }
+        score
+    };
+    side(game.turn()) - side(game.turn().opposite())
+}
+
+/// A bonus for a lone queen or rook against a lone king, for driving the
+/// enemy king to the edge of the board and bringing the other king close.
+/// That is how those endgames are won, but material alone cannot tell the
+/// engine which way to go. Scored like [`evaluate`].
+fn mop_up(game: &Game) -> i32 {
+    let board = game.board();
+    let (mut white_king, mut black_king) = ((0, 0), (0, 0));
+    let mut strong = None;
+    for y in 0..8 {
+        for x in 0..8 {
+            let piece = match board.get(x, y) {
+                Some(piece) => piece,
+                None => continue,
+            };
+            match piece.piece_type() {
+                PieceType::King if piece.color() == Color::White => {
+                    white_king = (x as i32, y as i32)
+                }
+                PieceType::King => black_king = (x as i32, y as i32),
+                PieceType::Queen | PieceType::Rook if strong.is_none() => {
+                    strong = Some(piece.color())
+                }
+                _ => return 0,
+            }
+        }
+    }
+    let strong = match strong {
+        Some(strong) => strong,
+        None => return 0,
+    };
+
+    let ((sx, sy), (wx, wy)) = match strong {
+        Color::White => (white_king, black_king),
+        Color::Black => (black_king, white_king),
+    };
+    // How far the weaker king is from the centre, counting in half squares
+    let edge = (2 * wx - 7).abs().max((2 * wy - 7).abs());
+    let closeness = 14 - ((wx - sx).abs() + (wy - sy).abs());
+    let bonus = 10 * edge + 4 * closeness;
+    if strong == game.turn() {
+        bonus
+    } else {
+        -bonus
+    }
+}
+
+/// Finds the best move by searching `depth` plies ahead. Returns [`None`]
+/// if the game has no legal moves.
+pub fn search(game: &Game, depth: usize) -> Option<Move> {
+    search_root(game, depth.max(1), None, None).map(|(m, _)| m)
+}
+
+/// Finds the best move it can within `max_millis` milliseconds, searching
+/// one ply deeper at a time and keeping the result of the deepest search
+/// that finished. Each search tries the previous best move first, which
+/// makes the deeper ones much faster. Returns [`None`] if the game has no
+/// legal moves.
+///
+/// The first ply is always searched in full, so a move is returned even
+/// with no time at all. This needs a working clock, which
+/// `wasm32-unknown-unknown` does not have; use [`search`] there instead.
+pub fn search_timed(game: &Game, max_millis: u64) -> Option<Move> {
+    let deadline = Instant::now() + Duration::from_millis(max_millis);
+    let (mut best, mut score) = search_root(game, 1, None, None)?;
+    for depth in 2..=MAX_DEPTH {
+        // A forced mate will not get any better by looking deeper
+        if score.is_mate() {
+            break;
+        }
+        match search_root(game, depth, Some(best), Some(deadline)) {
+            Some(result) => (best, score) = result,
+            None => break,
+        }
+    }
+    Some(best)
+}
+
+/// Searches every legal move to `depth` plies and returns the best one with
+/// its score. Returns [`None`] if there are no legal moves or the deadline
+/// passed before the search finished.
+fn search_root(
+    game: &Game,
+    depth: usize,
+    first: Option<Move>,
+    deadline: Option<Instant>,
+) -> Option<(Move, Score)> {
+    let mut best: Option<(Move, Score)> = None;
+    let mut alpha = -Score::INFINITY;
+    for m in ordered_moves(game, first) {
+        let mut next = game.clone();
+        next.make_legal_move(m);
+        let score = -negamax(&next, depth - 1, 1, -Score::INFINITY, -alpha, deadline)?;
+        if best.is_none_or(|(_, best_score)| score > best_score) {
+            best = Some((m, score));
+            alpha = alpha.max(score);
+        }
+    }
+    best
+}
+
+/// Scores a position `depth` plies deep with alpha-beta pruning, from the
+/// point of view of the player to move. `ply` is the distance from the
+/// root. Returns [`None`] once the deadline has passed.
+///
+/// Stalemate scores 0 like any other draw, so a player who is ahead steers
+/// away from stalemating the opponent, and one who is behind goes looking
+/// for it.
+fn negamax(
+    game: &Game,
+    depth: usize,
+    ply: usize,
+    mut alpha: Score,
+    beta: Score,
+    deadline: Option<Instant>,
+) -> Option<Score> {
+    if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+        return None;
+    }
+
+    let moves = ordered_moves(game, None);
+    if moves.is_empty() {
+        return Some(if game.is_in_check() {
+            -Score::mate_at_ply(ply)
+        } else {
+            Score::from(0)
+        });
+    }
+    if game.fifty_move_rule() >= 100
+        || game.repetition_count() >= 3
+        || game.board().is_insufficient_material()
+    {
+        return Some(Score::from(0));
+    }
+    if depth == 0 {
+        return Some(evaluate(game));
+    }
+
+    // If passing still leaves the opponent unable to reach beta, a real
+    // move would do at least as well, so the rest can be skipped. Without
+    // pieces besides pawns passing may really be the best move
+    // (zugzwang), so that is left out.
+    if depth > NULL_MOVE_REDUCTION && !beta.is_mate() && has_pieces(game) {
+        let mut next = game.clone();
+        if next.make_null_move().is_ok() {
+            let depth = depth - 1 - NULL_MOVE_REDUCTION;
+            let window = -Score::from(beta.centipawns() - 1);
+            let score = -negamax(&next, depth, ply + 1, -beta, window, deadline)?;
+            if score >= beta {
+                return Some(beta);
+            }
+        }
+    }
+
+    for m in moves {
+        let mut next = game.clone();
+        next.make_legal_move(m);
+        let score = -negamax(&next, depth - 1, ply + 1, -beta, -alpha, deadline)?;
+        if score >= beta {
+            return Some(beta);
+        }
+        alpha = alpha.max(score);
+    }
+    Some(alpha)
+}
+
+/// Checks whether the player to move has a piece other than pawns and
+/// the king.
+fn has_pieces(game: &Game) -> bool {
+    game.board().pieces().any(|(_, piece)| {
+        piece.color() == game.turn()
+            && !matches!(piece.piece_type(), PieceType::Pawn | PieceType::King)
+    })
+}
+
+/// The legal moves in the order they should be searched: `first`, then
+/// captures of the most valuable pieces, then everything else.
+fn ordered_moves(game: &Game, first: Option<Move>) -> Vec<Move> {
+    let mut moves = game.legal_moves();
+    moves.sort_by_key(|m| {
+        if Some(*m) == first {
+            i32::MIN
+        } else if game.is_capture(m) {
+            // An empty target square means en passant
+            let victim = game.board().get(m.to() % 8, m.to() / 8);
+            -victim
+                .map_or(PieceType::Pawn, |piece| piece.piece_type())
+                .value()
+        } else {
+            0
+        }
+    });
+    moves
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{GameStatus, STARTING_FEN};
+
+    #[test]
+    fn finds_mate_in_one() {
+        // Back rank mate: Ra8#
+        let game = Game::from_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1").unwrap();
+        assert_eq!(search(&game, 3).unwrap().to_uci(), "a1a8");
+        assert_eq!(search_timed(&game, 200).unwrap().to_uci(), "a1a8");
+    }
+
+    #[test]
+    fn timed_search_always_returns_a_legal_move() {
+        let game = Game::from_fen(STARTING_FEN).unwrap();
+        for millis in [0, 100] {
+            let m = search_timed(&game, millis).unwrap();
+            assert!(game.legal_moves().contains(&m));
+        }
+    }
+
+    #[test]
+    fn deeper_search_avoids_a_defended_pawn() {
+        // Qxd5 wins a pawn at depth 1, but cxd5 takes the queen back
+        let game = Game::from_fen("4k3/8/2p5/3p4/8/8/3Q4/4K3 w - - 0 1").unwrap();
+        assert_eq!(search(&game, 1).unwrap().to_uci(), "d2d5");
+        assert_ne!(search(&game, 2).unwrap().to_uci(), "d2d5");
+        assert_ne!(search_timed(&game, 500).unwrap().to_uci(), "d2d5");
+    }
+
+    #[test]
+    fn no_move_without_legal_moves() {
+        let game = Game::from_fen("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1").unwrap();
+        assert!(search(&game, 2).is_none());
+        assert!(search_timed(&game, 10).is_none());
+    }
+
+    /// Plays the engine against itself from `fen` and checks that it
+    /// reaches checkmate without ever drawing first.
+    fn converts(fen: &str) {
+        let mut game = Game::from_fen(fen).unwrap();
+        for _ in 0..100 {
+            if game.is_checkmate() {
+                return;
+            }
+            assert_eq!(game.status(), GameStatus::Ongoing, "{}", game.to_fen());
+            let m = search(&game, 4).unwrap();
+            game.make_move(m).unwrap();
+        }
+        panic!("No mate from {}", game.to_fen());
+    }
+
+    #[test]
+    fn mates_with_king_and_rook() {
+        converts("8/8/8/3k4/8/8/8/KR6 w - - 0 1");
+    }
+
+    #[test]
+    fn mop_up_drives_the_king_to_the_edge() {
+        let centre = Game::from_fen("8/8/8/3k4/8/8/8/KR6 w - - 0 1").unwrap();
+        let edge = Game::from_fen("3k4/8/8/8/8/8/8/KR6 w - - 0 1").unwrap();
+        assert!(mop_up(&edge) > mop_up(&centre));
+        assert!(mop_up(&centre) > 0);
+        // Scored for the player to move
+        let black = Game::from_fen("3k4/8/8/8/8/8/8/KR6 b - - 0 1").unwrap();
+        assert_eq!(mop_up(&black), -mop_up(&edge));
+        // Anything more than a lone rook or queen gets no bonus
+        let pawn = Game::from_fen("3k4/8/8/8/8/8/P7/KR6 w - - 0 1").unwrap();
+        assert_eq!(mop_up(&pawn), 0);
+    }
+
+    #[test]
+    fn cramped_side_scores_worse() {
+        // Material is level, but white has developed three pieces
+        let fen = "rnbqkbnr/pppppppp/8/8/2B1P3/2N2N2/PPPP1PPP/R1BQK2R";
+        let white = Game::from_fen(&format!("{} w KQkq - 0 1", fen)).unwrap();
+        let black = Game::from_fen(&format!("{} b KQkq - 0 1", fen)).unwrap();
+        let board = white.board();
+        assert!(board.mobility(Color::White) > board.mobility(Color::Black));
+        assert!(evaluate(&white) > Score::from(0));
+        assert!(evaluate(&black) < Score::from(0));
+        assert_eq!(evaluate(&white), -evaluate(&black));
+    }
+
+    #[test]
+    fn mates_with_king_and_queen() {
+        converts("8/8/8/3k4/8/8/8/KQ6 w - - 0 1");
+    }
+
+    #[test]
+    fn winning_side_avoids_stalemate() {
+        // Almost every white move leaves black stalemated
+        let fen = "K6k/5Q2/8/8/8/8/8/8 w - - 0 1";
+        let game = Game::from_fen(fen).unwrap();
+        let m = search(&game, 3).unwrap();
+        let mut next = game.clone();
+        next.make_move(m).unwrap();
+        assert_eq!(next.status(), GameStatus::Ongoing, "{}", m.to_uci());
+        converts(fen);
+    }
+
+    #[test]
+    fn losing_side_heads_for_stalemate() {
+        // Qb8+ Kxb8 leaves black with no moves
+        let game = Game::from_fen("K6k/R4Q2/1q6/8/8/8/8/8 b - - 0 1").unwrap();
+        assert_eq!(search(&game, 3).unwrap().to_uci(), "b6b8");
+    }
+}
@@ -0,0 +1,100 @@
+use std::fmt;
+use std::ops::Neg;
+
+use super::MAX_DEPTH;
+
+/// The score of delivering checkmate right now. Mates further away score
+/// a little less, so the search prefers the quickest one.
+const MATE: i32 = 100_000;
+
+/// The highest score that is not a mate. Centipawn scores are kept below
+/// it so no amount of material can be mistaken for a mate.
+const MAX_CENTIPAWNS: i32 = MATE - MAX_DEPTH as i32 - 1;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+/// How good a position is for the player to move: positive is good for
+/// them and negative is good for their opponent. Most scores are in
+/// centipawns, hundredths of a pawn, but forced mates score above or below
+/// every centipawn score, closer mates further out.
+pub struct Score(i32);
+
+impl Score {
+    /// A score beyond every other, for starting a search window.
+    pub(super) const INFINITY: Self = Self(MATE + 1);
+
+    /// The player to move mates in `moves` of their own moves.
+    pub fn mate_in(moves: u32) -> Self {
+        Self::mate_at_ply(2 * moves.max(1) as usize - 1)
+    }
+
+    /// The player to move is mated after `moves` of their own moves.
+    pub fn mated_in(moves: u32) -> Self {
+        -Self::mate_at_ply(2 * moves as usize)
+    }
+
+    /// The score of a checkmate delivered `ply` plies from the root of
+    /// the search, for the side delivering it.
+    pub(super) fn mate_at_ply(ply: usize) -> Self {
+        Self(MATE - ply as i32)
+    }
+
+    /// Checks whether this is a forced mate for either side, rather than
+    /// a centipawn score.
+    pub fn is_mate(self) -> bool {
+        self.0.abs() > MAX_CENTIPAWNS
+    }
+
+    /// The score in centipawns. Mates give a value above or below any
+    /// centipawn score.
+    pub fn centipawns(self) -> i32 {
+        self.0
+    }
+}
+
+impl From<i32> for Score {
+    /// A score of `centipawns`, held back from the values used for mates.
+    fn from(centipawns: i32) -> Self {
+        Self(centipawns.clamp(-MAX_CENTIPAWNS, MAX_CENTIPAWNS))
+    }
+}
+
+impl Neg for Score {
+    type Output = Self;
+
+    /// The same score from the opponent's point of view.
+    fn neg(self) -> Self {
+        Self(-self.0)
+    }
+}
+
+impl fmt::Display for Score {
+    /// Writes mates as `#3`, or `#-3` for being mated, and other scores
+    /// in centipawns.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if !self.is_mate() {
+            write!(f, "{}", self.0)
+        } else if self.0 > 0 {
+            write!(f, "#{}", (MATE - self.0 + 1) / 2)
+        } else {
+            write!(f, "#-{}", (MATE + self.0) / 2)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Score;
+
+    #[test]
+    fn score_mates() {
+        assert!(Score::mate_in(3) > Score::from(i32::MAX));
+        assert!(Score::mate_in(3) < Score::mate_in(2));
+        assert!(Score::mated_in(3) < Score::from(i32::MIN));
+        assert!(Score::mated_in(3) > Score::mated_in(2));
+        assert_eq!(Score::mate_in(3).to_string(), "#3");
+        assert_eq!(Score::mated_in(2).to_string(), "#-2");
+        assert_eq!(Score::from(35).to_string(), "35");
+        assert!(!Score::from(i32::MAX).is_mate());
+        assert!(Score::mate_in(1).is_mate());
+    }
+}
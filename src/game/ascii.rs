@@ -0,0 +1,80 @@
+use std::fmt;
+
+use super::{Board, Game, BOARD_SIZE};
+
+impl Board {
+    /// Draws the position as text, one rank per line from the 8th down,
+    /// with the pieces as FEN letters, empty squares as `.`, and the
+    /// squares in `marked` as `*`. Ranks are numbered on the left and
+    /// files lettered underneath.
+    fn to_ascii(&self, marked: &[(usize, usize)]) -> String {
+        let mut text = String::new();
+        for y in 0..BOARD_SIZE {
+            text.push_str(&(BOARD_SIZE - y).to_string());
+            for x in 0..BOARD_SIZE {
+                text.push(' ');
+                text.push(match self.get(x, y) {
+                    _ if marked.contains(&(x, y)) => '*',
+                    Some(piece) => piece.to_fen_char(),
+                    None => '.',
+                });
+            }
+            text.push('\n');
+        }
+        text.push_str("  a b c d e f g h");
+        text
+    }
+}
+
+impl fmt::Display for Board {
+    /// Draws the position as text, with white's pieces in uppercase:
+    ///
+    /// ```text
+    /// 8 r n b q k b n r
+    /// 7 p p p p p p p p
+    /// 6 . . . . . . . .
+    /// 5 . . . . . . . .
+    /// 4 . . . . . . . .
+    /// 3 . . . . . . . .
+    /// 2 P P P P P P P P
+    /// 1 R N B Q K B N R
+    ///   a b c d e f g h
+    /// ```
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_ascii(&[]))
+    }
+}
+
+impl Game {
+    /// Draws the board as text like its [`Display`](fmt::Display), with
+    /// the given coordinates marked by `*`, such as the squares a piece
+    /// can move to. Marks replace whatever stands on the square.
+    pub fn ascii_with_squares(&self, marked: &[(usize, usize)]) -> String {
+        self.board.to_ascii(marked)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game::{Board, Game, STARTING_FEN};
+
+    #[test]
+    fn ascii_marks() {
+        let game = Game::from_fen(STARTING_FEN).unwrap();
+        let targets: Vec<(usize, usize)> = game
+            .legal_moves_for(Board::c_to_i(6, 7))
+            .iter()
+            .map(|m| Board::i_to_c(m.to()))
+            .collect();
+        let text = game.ascii_with_squares(&targets);
+        assert_eq!(text.matches('*').count(), 2);
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines[5], "3 . . . . . * . *");
+        assert_eq!(lines[7], "1 R N B Q K B N R");
+        assert_eq!(lines[8], "  a b c d e f g h");
+
+        let plain = game.board().to_string();
+        assert!(!plain.contains('*'));
+        assert!(plain.starts_with("8 r n b q k b n r\n"));
+    }
+}
@@ -0,0 +1,93 @@
+use super::{Game, Move, STARTING_FEN};
+
+/// A handful of opening mainlines in UCI notation. Earlier lines take
+/// precedence when several continue from the same position.
+const BOOK: &[&[&str]] = &[
+    // Ruy Lopez
+    &[
+        "e2e4", "e7e5", "g1f3", "b8c6", "f1b5", "a7a6", "b5a4", "g8f6", "e1g1", "f8e7",
+    ],
+    // Italian Game
+    &[
+        "e2e4", "e7e5", "g1f3", "b8c6", "f1c4", "f8c5", "c2c3", "g8f6",
+    ],
+    // Sicilian Defence, Najdorf
+    &[
+        "e2e4", "c7c5", "g1f3", "d7d6", "d2d4", "c5d4", "f3d4", "g8f6", "b1c3", "a7a6",
+    ],
+    // French Defence
+    &["e2e4", "e7e6", "d2d4", "d7d5", "b1c3", "g8f6"],
+    // Caro-Kann Defence
+    &["e2e4", "c7c6", "d2d4", "d7d5", "b1c3", "d5e4", "c3e4"],
+    // Queen's Gambit Declined
+    &[
+        "d2d4", "d7d5", "c2c4", "e7e6", "b1c3", "g8f6", "c1g5", "f8e7",
+    ],
+    // Slav Defence
+    &["d2d4", "d7d5", "c2c4", "c7c6", "g1f3", "g8f6"],
+    // King's Indian Defence
+    &[
+        "d2d4", "g8f6", "c2c4", "g7g6", "b1c3", "f8g7", "e2e4", "d7d6",
+    ],
+    // English Opening
+    &["c2c4", "e7e5", "b1c3", "g8f6"],
+];
+
+impl Game {
+    /// Looks up a reply from the built-in opening book. Positions are
+    /// matched on piece placement, turn, and castling rights, so
+    /// transpositions into a book line are found too.
+    pub fn book_move(&self) -> Option<Move> {
+        let key = self.book_key();
+        for line in BOOK {
+            let mut game = match Game::from_fen(STARTING_FEN) {
+                Ok(game) => game,
+                Err(_) => return None,
+            };
+            for uci in line.iter() {
+                let m = match Move::from_uci(uci) {
+                    Ok(m) => m,
+                    Err(_) => break,
+                };
+                if game.book_key() == key {
+                    return Some(m);
+                }
+                if game.make_move(m).is_err() {
+                    break;
+                }
+            }
+        }
+        None
+    }
+
+    /// The part of the FEN string a book position is matched on.
+    fn book_key(&self) -> String {
+        let fen = self.to_fen();
+        fen.split(' ').take(3).collect::<Vec<_>>().join(" ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn book_lines_are_legal() {
+        for line in BOOK {
+            let mut game = Game::from_fen(STARTING_FEN).unwrap();
+            for uci in line.iter() {
+                game.make_move(Move::from_uci(uci).unwrap()).unwrap();
+            }
+        }
+    }
+
+    #[test]
+    fn book_follows_and_leaves_its_lines() {
+        let game = Game::from_fen(STARTING_FEN).unwrap();
+        assert_eq!(game.book_move().unwrap().to_uci(), "e2e4");
+        let game = game.with_move(Move::from_uci("e2e4").unwrap()).unwrap();
+        assert_eq!(game.book_move().unwrap().to_uci(), "e7e5");
+        let game = game.with_move(Move::from_uci("a7a6").unwrap()).unwrap();
+        assert!(game.book_move().is_none());
+    }
+}
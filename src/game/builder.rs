@@ -0,0 +1,250 @@
+use super::{Board, CastlingRights, Color, Game, PieceType, MAX_FULL_TURN, MAX_HALFMOVE_CLOCK};
+
+#[derive(Clone, Debug)]
+/// Sets up a [`Game`] one part at a time instead of from a FEN string.
+/// [`GameBuilder::build`] checks that the parts fit together.
+///
+/// ```
+/// use chess_rs::game::{Board, CastlingRights, Color, GameBuilder};
+///
+/// let game = GameBuilder::new(Board::from_fen("4k3/8/8/8/8/8/8/4K2R").unwrap())
+///     .turn(Color::Black)
+///     .castling(CastlingRights::WHITE_KINGSIDE)
+///     .build()
+///     .unwrap();
+/// assert_eq!(game.to_fen(), "4k3/8/8/8/8/8/8/4K2R b K - 0 1");
+/// ```
+pub struct GameBuilder {
+    board: Board,
+    turn: Color,
+    castling: CastlingRights,
+    en_passant: Option<String>,
+    fifty_move_rule: usize,
+    full_turn_num: usize,
+}
+
+impl GameBuilder {
+    /// Starts from a board with white to move, no castling rights or en
+    /// passant square, and the counters at the start of a game.
+    pub fn new(board: Board) -> Self {
+        Self {
+            board,
+            turn: Color::White,
+            castling: CastlingRights::empty(),
+            en_passant: None,
+            fifty_move_rule: 0,
+            full_turn_num: 1,
+        }
+    }
+
+    /// Sets the player to move.
+    pub fn turn(mut self, turn: Color) -> Self {
+        self.turn = turn;
+        self
+    }
+
+    /// Sets the castling rights. Rights written as in FEN, like `"KQq"`,
+    /// can be read with [`str::parse`].
+    pub fn castling(mut self, castling: CastlingRights) -> Self {
+        self.castling = castling;
+        self
+    }
+
+    /// Sets the square a pawn just skipped over with a double push, like
+    /// `"e3"`.
+    pub fn en_passant(mut self, square: &str) -> Self {
+        self.en_passant = Some(square.to_owned());
+        self
+    }
+
+    /// Sets the number of half moves since the last pawn move or capture.
+    pub fn fifty_move_rule(mut self, half_moves: usize) -> Self {
+        self.fifty_move_rule = half_moves;
+        self
+    }
+
+    /// Sets the number of the full move being played, starting at 1.
+    pub fn full_turn_num(mut self, full_turn_num: usize) -> Self {
+        self.full_turn_num = full_turn_num;
+        self
+    }
+
+    /// Checks that the parts describe a position that could come up in a
+    /// game, and starts a game from it. Each side must have one king, and
+    /// the player not to move must not be in check. Every castling right
+    /// needs the king and that rook on their starting squares, and an en
+    /// passant square needs the pawn that just skipped over it.
+    pub fn build(self) -> Result<Game, String> {
+        let board = self.board;
+        for color in [Color::White, Color::Black] {
+            let kings = board
+                .position
+                .iter()
+                .flatten()
+                .filter(|piece| piece.piece == PieceType::King && piece.color == color)
+                .count();
+            if kings != 1 {
+                return Err(format!("Expected one {:?} king, found {}", color, kings));
+            }
+        }
+        if let Some((x, y)) = board.king(self.turn.opposite()) {
+            if board.is_attacked(x, y, self.turn) {
+                return Err(format!(
+                    "{:?} is in check but it is not their turn",
+                    self.turn.opposite()
+                ));
+            }
+        }
+
+        for color in [Color::White, Color::Black] {
+            for (kingside, rook_x) in [(true, 7), (false, 0)] {
+                let right = CastlingRights::of(color, kingside);
+                let home_row = color.back_rank();
+                if self.castling.contains(right)
+                    && (!board.has(4, home_row, PieceType::King, color)
+                        || !board.has(rook_x, home_row, PieceType::Rook, color))
+                {
+                    return Err(format!(
+                        "Castling right {} needs the king and rook on their starting squares",
+                        right
+                    ));
+                }
+            }
+        }
+
+        let en_passant = match &self.en_passant {
+            None => None,
+            Some(square) => {
+                let index = Board::a_to_i(square)
+                    .ok_or_else(|| format!("Invalid en passant square: {}", square))?;
+                let (x, y) = Board::i_to_c(index);
+                // The pawn that just moved belongs to the player not to move
+                let mover = self.turn.opposite();
+                let skipped_row = mover.en_passant_rank();
+                let landed_row = (skipped_row as i32 + mover.pawn_direction()) as usize;
+                if y != skipped_row
+                    || !board.has(x, landed_row, PieceType::Pawn, mover)
+                    || board.is_occupied(x, skipped_row)
+                    || board.is_occupied(x, mover.pawn_start_rank())
+                {
+                    return Err(format!(
+                        "No {:?} pawn could have just skipped over {}",
+                        mover, square
+                    ));
+                }
+                Some(index)
+            }
+        };
+
+        if self.full_turn_num == 0 {
+            return Err("The full move number starts at 1".to_owned());
+        }
+        if self.full_turn_num > MAX_FULL_TURN {
+            return Err(format!("Full move number too high: {}", self.full_turn_num));
+        }
+        if self.fifty_move_rule > MAX_HALFMOVE_CLOCK {
+            return Err(format!(
+                "Fifty move rule count too high: {}",
+                self.fifty_move_rule
+            ));
+        }
+        Ok(Game::from_parts(
+            board,
+            self.turn,
+            self.castling,
+            en_passant,
+            self.fifty_move_rule,
+            self.full_turn_num,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::GameStatus;
+
+    fn board(fen: &str) -> Board {
+        Board::from_fen(fen).unwrap()
+    }
+
+    #[test]
+    fn builds_castling_rights() {
+        let game = GameBuilder::new(board("r3k2r/8/8/8/8/8/8/R3K2R"))
+            .castling("KQkq".parse().unwrap())
+            .fifty_move_rule(3)
+            .full_turn_num(7)
+            .build()
+            .unwrap();
+        assert_eq!(game.to_fen(), "r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 3 7");
+
+        // No rook on h1
+        let err = GameBuilder::new(board("r3k2r/8/8/8/8/8/8/R3K3"))
+            .castling(CastlingRights::WHITE_KINGSIDE | CastlingRights::WHITE_QUEENSIDE)
+            .build()
+            .unwrap_err();
+        assert!(err.contains("Castling right K"), "{}", err);
+        assert!("KK".parse::<CastlingRights>().is_err());
+    }
+
+    #[test]
+    fn checks_en_passant_square() {
+        let b = board("4k3/8/8/8/4P3/8/8/4K3");
+        let game = GameBuilder::new(b.clone())
+            .turn(Color::Black)
+            .en_passant("e3")
+            .build()
+            .unwrap();
+        assert_eq!(game.en_passant(), Board::a_to_i("e3"));
+        assert!(GameBuilder::new(b.clone())
+            .en_passant("e3")
+            .build()
+            .is_err());
+        assert!(GameBuilder::new(b.clone())
+            .turn(Color::Black)
+            .en_passant("d3")
+            .build()
+            .is_err());
+        assert!(GameBuilder::new(b)
+            .turn(Color::Black)
+            .en_passant("e6")
+            .build()
+            .is_err());
+    }
+
+    #[test]
+    fn rejects_impossible_positions() {
+        // Black in check with white to move
+        assert!(GameBuilder::new(board("4k3/8/8/8/8/8/8/4R1K1"))
+            .build()
+            .is_err());
+        assert!(GameBuilder::new(board("4k3/8/8/8/8/8/8/4R1K1"))
+            .turn(Color::Black)
+            .build()
+            .is_ok());
+        assert!(GameBuilder::new(board("8/8/8/8/8/8/8/K7")).build().is_err());
+    }
+
+    #[test]
+    fn bounds_move_counters() {
+        let b = board("4k3/8/8/8/8/8/8/4K2R");
+        assert!(GameBuilder::new(b.clone())
+            .full_turn_num(0)
+            .build()
+            .is_err());
+        assert!(GameBuilder::new(b.clone())
+            .full_turn_num(usize::MAX)
+            .build()
+            .is_err());
+        assert!(GameBuilder::new(b.clone())
+            .fifty_move_rule(usize::MAX)
+            .build()
+            .is_err());
+
+        let game = GameBuilder::new(b)
+            .fifty_move_rule(MAX_HALFMOVE_CLOCK)
+            .build()
+            .unwrap();
+        assert_eq!(game.status(), GameStatus::SeventyFiveMoveRule);
+    }
+}
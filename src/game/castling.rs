@@ -0,0 +1,161 @@
+use std::fmt;
+use std::ops::BitOr;
+use std::str::FromStr;
+
+use super::Color;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+/// Who can still castle where. Combine rights with `|`.
+pub struct CastlingRights(u8);
+
+impl CastlingRights {
+    /// White can castle kingside, `K` in FEN.
+    pub const WHITE_KINGSIDE: Self = Self(1);
+    /// White can castle queenside, `Q` in FEN.
+    pub const WHITE_QUEENSIDE: Self = Self(1 << 1);
+    /// Black can castle kingside, `k` in FEN.
+    pub const BLACK_KINGSIDE: Self = Self(1 << 2);
+    /// Black can castle queenside, `q` in FEN.
+    pub const BLACK_QUEENSIDE: Self = Self(1 << 3);
+    /// Both sides can castle both ways, as at the start of a game.
+    pub const ALL: Self = Self(0b1111);
+
+    /// Each single right, in `KQkq` order.
+    pub(super) const EACH: [Self; 4] = [
+        Self::WHITE_KINGSIDE,
+        Self::WHITE_QUEENSIDE,
+        Self::BLACK_KINGSIDE,
+        Self::BLACK_QUEENSIDE,
+    ];
+
+    /// No rights at all, written `-` in FEN.
+    pub fn empty() -> Self {
+        Self(0)
+    }
+
+    /// The right of `color` to castle on one side.
+    pub fn of(color: Color, kingside: bool) -> Self {
+        match (color, kingside) {
+            (Color::White, true) => Self::WHITE_KINGSIDE,
+            (Color::White, false) => Self::WHITE_QUEENSIDE,
+            (Color::Black, true) => Self::BLACK_KINGSIDE,
+            (Color::Black, false) => Self::BLACK_QUEENSIDE,
+        }
+    }
+
+    /// Both rights of `color`.
+    pub fn both(color: Color) -> Self {
+        Self::of(color, true) | Self::of(color, false)
+    }
+
+    /// Checks whether every right in `other` is held.
+    pub fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// Checks whether nobody can castle.
+    pub fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    /// Takes away every right in `other`.
+    pub fn remove(&mut self, other: Self) {
+        self.0 &= !other.0;
+    }
+
+    /// Reads a single right from its FEN letter, one of `KQkq`.
+    pub fn from_char(c: char) -> Option<Self> {
+        match c {
+            'K' => Some(Self::WHITE_KINGSIDE),
+            'Q' => Some(Self::WHITE_QUEENSIDE),
+            'k' => Some(Self::BLACK_KINGSIDE),
+            'q' => Some(Self::BLACK_QUEENSIDE),
+            _ => None,
+        }
+    }
+
+    /// Writes the rights as in FEN: some of `KQkq` in that order, or `-`
+    /// for none.
+    pub fn to_fen(self) -> String {
+        if self.is_empty() {
+            return "-".to_owned();
+        }
+        "KQkq"
+            .chars()
+            .zip(Self::EACH)
+            .filter(|&(_, right)| self.contains(right))
+            .map(|(c, _)| c)
+            .collect()
+    }
+}
+
+impl BitOr for CastlingRights {
+    type Output = Self;
+
+    fn bitor(self, other: Self) -> Self {
+        Self(self.0 | other.0)
+    }
+}
+
+impl FromStr for CastlingRights {
+    type Err = String;
+
+    /// Parses the castling part of a FEN, such as `KQkq`, `Kq`, or `-`.
+    /// The letters may come in any order, but not twice.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s == "-" {
+            return Ok(Self::empty());
+        }
+        let mut rights = Self::empty();
+        for c in s.chars() {
+            let right = Self::from_char(c)
+                .ok_or_else(|| format!("Invalid char in castling part: {}", c))?;
+            if rights.contains(right) {
+                return Err(format!("Duplicate char in castling part: {}", c));
+            }
+            rights = rights | right;
+        }
+        Ok(rights)
+    }
+}
+
+impl fmt::Display for CastlingRights {
+    /// Writes the rights as in FEN, like [`CastlingRights::to_fen`].
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_fen())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game::{CastlingRights, Game};
+
+    #[test]
+    fn castling_rights_round_trip() {
+        for fen in ["KQkq", "Kq", "-", "k"] {
+            let rights: CastlingRights = fen.parse().unwrap();
+            assert_eq!(rights.to_string(), fen);
+        }
+        assert_eq!("qK".parse::<CastlingRights>().unwrap().to_fen(), "Kq");
+        assert_eq!(
+            "KQkq".parse::<CastlingRights>().unwrap(),
+            CastlingRights::ALL
+        );
+        assert!("KK".parse::<CastlingRights>().is_err());
+        assert!("X".parse::<CastlingRights>().is_err());
+
+        let game = Game::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w Kq - 0 1").unwrap();
+        assert_eq!(
+            game.castling_rights(),
+            CastlingRights::WHITE_KINGSIDE | CastlingRights::BLACK_QUEENSIDE
+        );
+        assert_eq!(game.to_fen(), "r3k2r/8/8/8/8/8/8/R3K2R w Kq - 0 1");
+
+        let mut game = Game::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1").unwrap();
+        game.make_move_san("Rxa8").unwrap();
+        assert_eq!(game.castling_rights().to_fen(), "Kk");
+        game.make_move_san("Ke7").unwrap();
+        assert_eq!(game.castling_rights().to_fen(), "K");
+        assert_eq!(game.hash(), game.position_hash());
+    }
+}
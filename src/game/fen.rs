@@ -0,0 +1,258 @@
+use std::fmt;
+
+use super::{Board, Piece, MAX_FULL_TURN, MAX_HALFMOVE_CLOCK};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+/// A problem found in a Forsyth-Edwards Notation (FEN) string
+pub enum FenError {
+    /// There was nothing to parse.
+    Empty,
+    /// The piece placement did not have exactly 8 ranks.
+    RankCount(usize),
+    /// A rank (numbered 8 down to 1) did not describe exactly 8 squares.
+    RankLength {
+        rank: usize,
+        squares: usize,
+    },
+    /// A character in the piece placement was not a piece or digit.
+    InvalidPiece(char),
+    /// A side did not have exactly one king.
+    KingCount {
+        white: usize,
+        black: usize,
+    },
+    InvalidTurn(String),
+    InvalidCastling(String),
+    InvalidEnPassant(String),
+    InvalidHalfmoveClock(String),
+    InvalidFullmoveNumber(String),
+    /// There were more than the six standard fields.
+    TooManyFields(usize),
+}
+
+impl fmt::Display for FenError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FenError::Empty => write!(f, "No FEN?"),
+            FenError::RankCount(count) => write!(f, "Expected 8 ranks, found {}", count),
+            FenError::RankLength { rank, squares } => {
+                write!(f, "Rank {} has {} squares instead of 8", rank, squares)
+            }
+            FenError::InvalidPiece(c) => write!(f, "Invalid char in FEN: {}", c),
+            FenError::KingCount { white, black } => write!(
+                f,
+                "Expected one king per side, found {} white and {} black",
+                white, black
+            ),
+            FenError::InvalidTurn(turn) => write!(f, "Invalid turn specifier: {}", turn),
+            FenError::InvalidCastling(castling) => {
+                write!(f, "Invalid castling part: {}", castling)
+            }
+            FenError::InvalidEnPassant(square) => {
+                write!(f, "Invalid en passant square: {}", square)
+            }
+            FenError::InvalidHalfmoveClock(clock) => {
+                write!(f, "Invalid halfmove clock: {}", clock)
+            }
+            FenError::InvalidFullmoveNumber(number) => {
+                write!(f, "Invalid fullmove number: {}", number)
+            }
+            FenError::TooManyFields(count) => write!(f, "Expected 6 fields, found {}", count),
+        }
+    }
+}
+
+impl std::error::Error for FenError {}
+
+impl Board {
+    /// Checks a whole FEN string and reports every problem found, rather
+    /// than stopping at the first like [`Board::from_fen`]. An empty list
+    /// means the string is valid. Missing trailing fields are allowed,
+    /// matching the parser's defaults.
+    pub fn validate_fen(fen: &str) -> Vec<FenError> {
+        let mut errors = Vec::new();
+        let fields: Vec<&str> = fen.split_whitespace().collect();
+        let placement = match fields.first() {
+            Some(&placement) if !placement.is_empty() => placement,
+            _ => return vec![FenError::Empty],
+        };
+
+        let ranks: Vec<&str> = placement.split('/').collect();
+        if ranks.len() != 8 {
+            errors.push(FenError::RankCount(ranks.len()));
+        }
+        let (mut white_kings, mut black_kings) = (0, 0);
+        for (i, rank) in ranks.iter().enumerate() {
+            let mut squares = 0;
+            for c in rank.chars() {
+                match c {
+                    '1'..='8' => squares += c as usize - '0' as usize,
+                    'K' => {
+                        white_kings += 1;
+                        squares += 1;
+                    }
+                    'k' => {
+                        black_kings += 1;
+                        squares += 1;
+                    }
+                    _ if Piece::from_fen_char(c).is_some() => squares += 1,
+                    _ => errors.push(FenError::InvalidPiece(c)),
+                }
+            }
+            if squares != 8 {
+                errors.push(FenError::RankLength {
+                    rank: 8usize.saturating_sub(i),
+                    squares,
+                });
+            }
+        }
+        if white_kings != 1 || black_kings != 1 {
+            errors.push(FenError::KingCount {
+                white: white_kings,
+                black: black_kings,
+            });
+        }
+
+        if let Some(&turn) = fields.get(1) {
+            if turn != "w" && turn != "b" {
+                errors.push(FenError::InvalidTurn(turn.to_owned()));
+            }
+        }
+        if let Some(&castling) = fields.get(2) {
+            let mut seen = Vec::new();
+            let valid = castling == "-"
+                || (!castling.is_empty()
+                    && castling.chars().all(|c| {
+                        let fresh = "KQkq".contains(c) && !seen.contains(&c);
+                        seen.push(c);
+                        fresh
+                    }));
+            if !valid {
+                errors.push(FenError::InvalidCastling(castling.to_owned()));
+            }
+        }
+        if let Some(&square) = fields.get(3) {
+            // En passant squares are on the 3rd rank after a white pawn's
+            // double push, with black to move, and the 6th after a black one
+            let rank = if fields.get(1) == Some(&"b") {
+                '3'
+            } else {
+                '6'
+            };
+            let valid = square == "-" || (Self::a_to_i(square).is_some() && square.ends_with(rank));
+            if !valid {
+                errors.push(FenError::InvalidEnPassant(square.to_owned()));
+            }
+        }
+        if let Some(&clock) = fields.get(4) {
+            if !matches!(clock.parse::<usize>(), Ok(n) if n <= MAX_HALFMOVE_CLOCK) {
+                errors.push(FenError::InvalidHalfmoveClock(clock.to_owned()));
+            }
+        }
+        if let Some(&number) = fields.get(5) {
+            if !matches!(number.parse::<usize>(), Ok(n) if n <= MAX_FULL_TURN) {
+                errors.push(FenError::InvalidFullmoveNumber(number.to_owned()));
+            }
+        }
+        if fields.len() > 6 {
+            errors.push(FenError::TooManyFields(fields.len()));
+        }
+
+        errors
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{Game, STARTING_FEN};
+
+    #[test]
+    fn valid_fens_have_no_errors() {
+        assert!(Board::validate_fen(STARTING_FEN).is_empty());
+        assert!(Board::validate_fen("4k3/8/8/8/8/8/8/4K3").is_empty());
+        assert!(Board::validate_fen("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 2").is_empty());
+    }
+
+    #[test]
+    fn validator_agrees_with_the_parser() {
+        for fen in [
+            STARTING_FEN,
+            "4k3/8/8/8/8/8/8/4K3 w - - 0 0",
+            "4k3/8/8/8/8/8/8/4K3 b - - 12",
+            "4k3/8/8/8/8/8/8/4K3",
+            "4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 2",
+            "4k3/8/8/3pP3/8/8/8/4K3 w - d3 0 2",
+            "4k3/8/8/8/8/8/8/4K3 w - - 0 -1",
+            "4k3/8/8/8/8/8/8/4K3 w - - 0 99999999999999999999999",
+            "4k3/8/8/8/8/8/8/4K3 w - - 99999 1",
+            "4k3/8/8/8/8/8/8/4K3 w KK - 0 1",
+            "4k3/8/8/8/8/8/8/4K3 w - - 0 1 extra",
+            "4k3/8/8/8/8/8/8/4K3 - - - 0 1",
+            "4k3/8/8/8/8/8/8/4K4 w - - 0 1",
+            "8/8/8/8/8/8/8/4K3 w - - 0 1",
+            "",
+        ] {
+            assert_eq!(
+                Board::validate_fen(fen).is_empty(),
+                Game::from_fen(fen).is_ok(),
+                "{}",
+                fen
+            );
+        }
+    }
+
+    #[test]
+    fn reports_every_error() {
+        let errors =
+            Board::validate_fen("rnbqkbnr/ppxppppp/9/8/8/PPPPPPPP/RNBQKBNR x KQkk e5 -1 x");
+        assert_eq!(
+            errors,
+            [
+                FenError::RankCount(7),
+                FenError::InvalidPiece('x'),
+                FenError::RankLength {
+                    rank: 7,
+                    squares: 7
+                },
+                FenError::InvalidPiece('9'),
+                FenError::RankLength {
+                    rank: 6,
+                    squares: 0
+                },
+                FenError::InvalidTurn("x".to_owned()),
+                FenError::InvalidCastling("KQkk".to_owned()),
+                FenError::InvalidEnPassant("e5".to_owned()),
+                FenError::InvalidHalfmoveClock("-1".to_owned()),
+                FenError::InvalidFullmoveNumber("x".to_owned()),
+            ]
+        );
+        assert_eq!(Board::validate_fen("  "), [FenError::Empty]);
+        assert_eq!(
+            Board::validate_fen("8/8/8/8/8/8/8/8 w - - 0 1"),
+            [FenError::KingCount { white: 0, black: 0 }]
+        );
+        assert_eq!(
+            Board::validate_fen(&format!("{} extra", STARTING_FEN)),
+            [FenError::TooManyFields(7)]
+        );
+    }
+
+    #[test]
+    fn fen_whitespace() {
+        let game = Game::from_fen(" rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR  w KQkq\t-  0 1\n")
+            .unwrap();
+        assert_eq!(game.to_fen(), STARTING_FEN);
+        assert!(Board::validate_fen(
+            "  rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w  KQkq - 0 1\n"
+        )
+        .is_empty());
+        assert!(Game::from_fen("   ").is_err());
+        assert!(
+            Game::from_fen(" rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR  x KQkq - 0 1").is_err()
+        );
+        assert!(
+            Game::from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - zero 1").is_err()
+        );
+    }
+}
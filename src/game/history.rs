@@ -0,0 +1,274 @@
+use super::{Board, CastlingRights, Color, Game, Move, MoveFlags, Piece, PieceType};
+
+/// What a move changed that cannot be worked out from the move itself,
+/// kept so the move can be taken back without replaying the game.
+#[derive(Clone, Debug)]
+pub(super) struct UndoInfo {
+    /// The piece the move captured, if any.
+    pub(super) captured: Option<Piece>,
+    /// The castling rights before the move.
+    pub(super) castling: CastlingRights,
+    /// The en passant square before the move.
+    pub(super) en_passant: Option<usize>,
+    /// The fifty move rule count before the move.
+    pub(super) fifty_move_rule: usize,
+    /// The Zobrist hash before the move.
+    pub(super) hash: u64,
+    /// The last irreversible ply before the move.
+    pub(super) last_irreversible_ply: usize,
+    /// The repetition history the move cleared, if it was irreversible.
+    pub(super) position_history: Option<Vec<u64>>,
+}
+
+impl Game {
+    /// The number of half moves played since the start of the game.
+    pub fn ply(&self) -> usize {
+        self.moves.len()
+    }
+
+    /// Every move played since the start of the game, in order.
+    pub fn history(&self) -> &[Move] {
+        &self.moves
+    }
+
+    /// The last move played, or [`None`] at the start of the game.
+    pub fn last_move(&self) -> Option<Move> {
+        self.moves.last().copied()
+    }
+
+    /// The ply reached by the most recent pawn move, capture, or castle,
+    /// none of which can ever be undone by later moves. Positions from
+    /// before it cannot come up again. This is 0 if there has not been one
+    /// since the start of the game.
+    pub fn last_irreversible_ply(&self) -> usize {
+        self.last_irreversible_ply
+    }
+
+    /// Checks whether there is a move to take back, which there is not
+    /// at the position the game started from.
+    pub fn can_undo(&self) -> bool {
+        !self.moves.is_empty()
+    }
+
+    /// Takes back the last move played, returning it, or [`None`] at the
+    /// start of the game.
+    pub fn undo(&mut self) -> Option<Move> {
+        if !self.can_undo() {
+            return None;
+        }
+        let last = self.moves[self.ply() - 1];
+        self.undo_to(self.ply() - 1).ok()?;
+        Some(last)
+    }
+
+    /// Takes back moves until only the first `ply` half moves of the game
+    /// have been played, restoring the position, castling rights, en
+    /// passant square, and counters from that point. Each move is unwound
+    /// from the end, so going back a few moves is cheap however long the
+    /// game is. Passes made with [`Game::make_null_move`] after that point
+    /// are taken back too. A claimed draw is withdrawn.
+    pub fn undo_to(&mut self, ply: usize) -> Result<(), String> {
+        if ply > self.ply() {
+            return Err(format!(
+                "Cannot undo to ply {}, only {} have been played",
+                ply,
+                self.ply()
+            ));
+        }
+        while self.ply() > ply {
+            while matches!(self.null_moves.last(), Some(&(pass, _)) if pass == self.ply()) {
+                self.undo_null_move()?;
+            }
+            self.unmake_move();
+        }
+        self.draw_claim = None;
+        Ok(())
+    }
+
+    /// Takes back the last move, which must not have been followed by a
+    /// pass, using what [`UndoInfo`] kept about it.
+    fn unmake_move(&mut self) {
+        let (m, undo) = match (self.moves.pop(), self.undo_info.pop()) {
+            (Some(m), Some(undo)) => (m, undo),
+            _ => return,
+        };
+        let (from_x, from_y) = Board::i_to_c(m.from);
+        let (to_x, _) = Board::i_to_c(m.to);
+
+        let mut piece = self.set_square(m.to, None);
+        if m.promotion.is_some() {
+            piece = piece.map(|piece| Piece {
+                piece: PieceType::Pawn,
+                color: piece.color,
+            });
+        }
+        self.set_square(m.from, piece);
+        if m.flags.contains(MoveFlags::EN_PASSANT) {
+            self.set_square(Board::c_to_i(to_x, from_y), undo.captured);
+        } else {
+            self.set_square(m.to, undo.captured);
+        }
+        if m.is_castle() {
+            let (rook_from, rook_to) = if to_x > from_x { (7, 5) } else { (0, 3) };
+            let rook = self.set_square(Board::c_to_i(rook_to, from_y), None);
+            self.set_square(Board::c_to_i(rook_from, from_y), rook);
+        }
+
+        self.turn = self.turn.opposite();
+        self.half_turn_num -= 1;
+        if self.turn == Color::Black {
+            self.full_turn_num -= 1;
+        }
+        self.castling = undo.castling;
+        self.en_passant = undo.en_passant;
+        self.fifty_move_rule = undo.fifty_move_rule;
+        self.hash = undo.hash;
+        self.last_irreversible_ply = undo.last_irreversible_ply;
+        self.position_history.pop();
+        if let Some(history) = undo.position_history {
+            self.position_history = history;
+        }
+    }
+
+    /// A new game from the position this one started from.
+    pub(super) fn start(&self) -> Game {
+        Game::from_fen(&self.start_fen).expect("the starting FEN was written by to_fen")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game::{Game, Move, STARTING_FEN};
+
+    const LINE: [&str; 10] = [
+        "e2e4", "e7e5", "g1f3", "b8c6", "f1c4", "g8f6", "e1g1", "f8c5", "d2d4", "e5d4",
+    ];
+
+    /// Plays [`LINE`] from the start, returning the game and its FEN after
+    /// each ply.
+    fn played() -> (Game, Vec<String>) {
+        let mut game = Game::from_fen(STARTING_FEN).unwrap();
+        let mut fens = vec![game.to_fen()];
+        for uci in LINE {
+            game.make_move(Move::from_uci(uci).unwrap()).unwrap();
+            fens.push(game.to_fen());
+        }
+        (game, fens)
+    }
+
+    #[test]
+    fn undo_to_restores_earlier_positions() {
+        let (mut game, fens) = played();
+        assert_eq!(game.ply(), 10);
+        game.undo_to(4).unwrap();
+        assert_eq!(game.to_fen(), fens[4]);
+        assert_eq!(game.ply(), 4);
+        assert!(game.undo_to(5).is_err());
+        assert_eq!(game.undo(), Some(Move::from_uci("b8c6").unwrap()));
+        assert_eq!(game.to_fen(), fens[3]);
+        game.undo_to(0).unwrap();
+        assert_eq!(game.to_fen(), STARTING_FEN);
+        assert_eq!(game.undo(), None);
+        assert_eq!(game.repetition_count(), 1);
+    }
+
+    #[test]
+    fn undo_unwinds_special_moves() {
+        // Castling, en passant, and a promotion that captures
+        let fen = "r3k2r/1P1p4/8/4P3/8/8/8/R3K2R b KQkq - 0 1";
+        let mut game = Game::from_fen(fen).unwrap();
+        let hash = game.hash();
+        let material = game.material_balance();
+        for uci in ["d7d5", "e5d6", "e8g8", "e1c1", "g8g7", "b7a8q"] {
+            let m = Move::from_uci(uci).unwrap();
+            let before = game.to_fen();
+            game.make_move(m).unwrap();
+            game.undo();
+            assert_eq!(game.to_fen(), before, "{}", uci);
+            assert_eq!(game.hash(), game.position_hash(), "{}", uci);
+            game.make_move(m).unwrap();
+        }
+        game.undo_to(0).unwrap();
+        assert_eq!(game.to_fen(), fen);
+        assert_eq!(game.hash(), hash);
+        assert_eq!(game.material_balance(), material);
+        assert_eq!(game.repetition_count(), 1);
+    }
+
+    #[test]
+    fn undo_matches_replaying_the_game() {
+        for step in 1..20 {
+            // Walk through the legal moves in a different order each time
+            let mut game = Game::from_fen(STARTING_FEN).unwrap();
+            let mut states = vec![];
+            for ply in 0..80 {
+                let moves = game.legal_moves();
+                if moves.is_empty() {
+                    break;
+                }
+                states.push((game.to_fen(), game.hash(), game.position_history.clone()));
+                game.make_move(moves[ply * step % moves.len()]).unwrap();
+            }
+            while let Some(state) = states.pop() {
+                game.undo();
+                assert_eq!(
+                    (game.to_fen(), game.hash(), game.position_history.clone()),
+                    state
+                );
+                assert_eq!(game.material_balance(), game.board.material_balance());
+            }
+        }
+    }
+
+    #[test]
+    fn history_lists_moves_in_order() {
+        let mut game = Game::from_moves(&["e4", "e5", "Nf3"]).unwrap();
+        let history: Vec<String> = game.history().iter().map(|m| m.to_uci()).collect();
+        assert_eq!(history, ["e2e4", "e7e5", "g1f3"]);
+        assert_eq!(game.last_move(), Some(Move::from_uci("g1f3").unwrap()));
+        game.undo();
+        assert_eq!(game.history().len(), 2);
+        assert_eq!(game.last_move(), Some(Move::from_uci("e7e5").unwrap()));
+        assert_eq!(Game::from_fen(STARTING_FEN).unwrap().last_move(), None);
+    }
+
+    #[test]
+    fn last_irreversible_ply_follows_pawn_moves_captures_and_castling() {
+        let mut game = Game::from_moves(&["e4", "d5", "Nf3"]).unwrap();
+        assert_eq!(game.last_irreversible_ply(), 2);
+        game.make_move_san("dxe4").unwrap();
+        assert_eq!(game.last_irreversible_ply(), 4);
+        for san in ["Ng1", "Nf6", "Nf3", "Ng8"] {
+            game.make_move_san(san).unwrap();
+        }
+        assert_eq!(game.last_irreversible_ply(), 4);
+        game.undo_to(3).unwrap();
+        assert_eq!(game.last_irreversible_ply(), 2);
+
+        let mut game = Game::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 3 1").unwrap();
+        assert_eq!(game.last_irreversible_ply(), 0);
+        game.make_move_san("Kf1").unwrap();
+        assert_eq!(game.last_irreversible_ply(), 0);
+        game.make_move_san("O-O").unwrap();
+        assert_eq!(game.last_irreversible_ply(), 2);
+    }
+
+    #[test]
+    fn undo_stops_at_start() {
+        let fen = "rnbqkbnr/pppp1ppp/8/4p3/4P3/8/PPPP1PPP/RNBQKBNR w KQkq e6 0 7";
+        let mut game = Game::from_fen(fen).unwrap();
+        assert!(!game.can_undo());
+        assert!(game.undo().is_none());
+        game.make_move_san("Nf3").unwrap();
+        game.make_move_san("Nc6").unwrap();
+        assert!(game.can_undo());
+        assert!(game.undo().is_some());
+        assert!(game.undo().is_some());
+        assert!(!game.can_undo());
+        assert!(game.undo().is_none());
+        assert_eq!(game.ply(), 0);
+        assert_eq!(game.to_fen(), fen);
+        assert_eq!(game.start_turn(), 7);
+        assert!(game.undo_to(1).is_err());
+    }
+}
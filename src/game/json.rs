@@ -0,0 +1,109 @@
+//! Saving whole games as JSON, for storage where a structured format is
+//! easier to work with than PGN.
+
+use serde::de::Error as _;
+use serde::ser::Error as _;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use super::{DrawClaim, Game, Move, PgnHeaders};
+
+#[derive(Serialize, Deserialize)]
+/// What gets written for a [`Game`]. The position is not saved, since
+/// replaying the moves from `start_fen` gets it back.
+struct SavedGame {
+    start_fen: String,
+    /// The moves played, in UCI notation.
+    moves: Vec<String>,
+    /// The PGN result token, kept so the file can be read on its own.
+    result: String,
+    headers: PgnHeaders,
+    draw_claim: Option<DrawClaim>,
+}
+
+impl Serialize for Game {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        // A pass cannot be replayed by make_move
+        if !self.null_moves.is_empty() {
+            return Err(S::Error::custom("Cannot save a game with null moves"));
+        }
+        SavedGame {
+            start_fen: self.start_fen.clone(),
+            moves: self.moves.iter().map(|m| m.to_uci()).collect(),
+            result: self.result().to_pgn_token().to_owned(),
+            headers: self.headers.clone(),
+            draw_claim: self.draw_claim,
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Game {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let saved = SavedGame::deserialize(deserializer)?;
+        saved.into_game().map_err(D::Error::custom)
+    }
+}
+
+impl SavedGame {
+    /// Replays the saved moves and checks the result still matches.
+    fn into_game(self) -> Result<Game, String> {
+        let mut game = Game::from_fen(&self.start_fen).map_err(|e| e.to_string())?;
+        for uci in &self.moves {
+            game.make_move(Move::from_uci(uci)?)?;
+        }
+        if let Some(claim) = self.draw_claim {
+            game.claim_draw(claim)?;
+        }
+        if game.result().to_pgn_token() != self.result {
+            return Err(format!(
+                "Saved result does not match the moves: {}",
+                self.result
+            ));
+        }
+        game.headers = self.headers;
+        Ok(game)
+    }
+}
+
+impl Game {
+    /// Saves the game as JSON: the starting position, the moves in UCI
+    /// notation, the result, and the PGN headers. Games with null moves
+    /// cannot be saved.
+    pub fn to_json(&self) -> Result<String, String> {
+        serde_json::to_string(self).map_err(|e| e.to_string())
+    }
+
+    /// Loads a game saved by [`Game::to_json`], replaying its moves.
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        serde_json::from_str(json).map_err(|e| e.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game::{DrawClaim, Game, GameResult, STARTING_FEN};
+
+    #[test]
+    fn json_round_trip() {
+        let game = Game::from_pgn("[White \"Alice\"]\n\n1. f3 e5 2. g4 Qh4# 0-1").unwrap();
+        let json = game.to_json().unwrap();
+        assert!(json.contains("\"result\":\"0-1\""));
+        let back = Game::from_json(&json).unwrap();
+        assert_eq!(back.history().len(), 4);
+        assert_eq!(back.to_fen(), game.to_fen());
+        assert_eq!(back.result(), GameResult::BlackWins);
+        assert_eq!(back.headers().white, "Alice");
+
+        let mut game = Game::from_fen("4k3/8/8/8/8/8/8/R3K3 w - - 99 80").unwrap();
+        game.make_move_san("Ra2").unwrap();
+        game.claim_draw(DrawClaim::FiftyMoveRule).unwrap();
+        let back = Game::from_json(&game.to_json().unwrap()).unwrap();
+        assert_eq!(back.result(), GameResult::Draw);
+
+        assert!(Game::from_json(&json.replace("\"0-1\"", "\"1-0\"")).is_err());
+        assert!(Game::from_json("{}").is_err());
+        let mut game = Game::from_fen(STARTING_FEN).unwrap();
+        game.make_null_move().unwrap();
+        assert!(game.to_json().is_err());
+    }
+}
@@ -0,0 +1,60 @@
+use super::{Board, Color, Game, Piece};
+
+impl Piece {
+    /// The piece's value, positive for white and negative for black.
+    pub(super) fn signed_value(self) -> i32 {
+        match self.color {
+            Color::White => self.piece.value(),
+            Color::Black => -self.piece.value(),
+        }
+    }
+}
+
+impl Board {
+    /// Adds up the value of white's pieces and takes away the value of
+    /// black's, in centipawns.
+    pub fn material_balance(&self) -> i32 {
+        self.position
+            .iter()
+            .flatten()
+            .map(|piece| piece.signed_value())
+            .sum()
+    }
+}
+
+impl Game {
+    /// The material balance of the position, like
+    /// [`Board::material_balance`] but kept up to date as moves are made
+    /// instead of counted each time.
+    pub fn material_balance(&self) -> i32 {
+        self.material
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game::{Game, STARTING_FEN};
+
+    #[test]
+    fn material_follows_captures_and_promotions() {
+        let mut game = Game::from_fen(STARTING_FEN).unwrap();
+        assert_eq!(game.material_balance(), 0);
+        game.make_move_uci("e2e4").unwrap();
+        game.make_move_uci("d7d5").unwrap();
+        game.make_move_uci("e4d5").unwrap();
+        assert_eq!(game.material_balance(), 100);
+        assert_eq!(game.hash(), game.position_hash());
+        game.make_move_uci("d8d5").unwrap();
+        assert_eq!(game.material_balance(), 0);
+        game.undo();
+        assert_eq!(game.material_balance(), 100);
+
+        // Taking the rook while promoting to a queen gains both
+        let mut game = Game::from_fen("3r3k/4P3/8/8/8/8/8/K7 w - - 0 1").unwrap();
+        assert_eq!(game.material_balance(), -400);
+        game.make_move_uci("e7d8q").unwrap();
+        assert_eq!(game.material_balance(), 900);
+        assert_eq!(game.material_balance(), game.board().material_balance());
+        assert_eq!(game.hash(), game.position_hash());
+    }
+}
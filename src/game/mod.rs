@@ -1,15 +1,132 @@
-use std::collections::VecDeque;
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::ops::BitOr;
+use std::str::FromStr;
 
-#[derive(Clone, Copy, Debug)]
+use history::UndoInfo;
+
+mod ascii;
+mod book;
+mod builder;
+mod castling;
+mod fen;
+mod history;
+#[cfg(feature = "serde")]
+mod json;
+mod material;
+mod movegen;
+mod null;
+mod packed;
+mod pawns;
+mod perft;
+mod pgn;
+mod phase;
+mod repetition;
+mod san;
+mod see;
+mod selfplay;
+mod status;
+mod svg;
+mod tree;
+mod zobrist;
+
+pub use builder::GameBuilder;
+pub use castling::CastlingRights;
+pub use fen::FenError;
+pub use pgn::{MoveAnnotations, PgnEval, PgnHeaders};
+pub use phase::{Phase, FULL_PHASE};
+pub use repetition::PositionKey;
+pub use status::{DrawClaim, GameResult, GameStatus};
+pub use tree::GameTree;
+pub use zobrist::ZOBRIST_SEED;
+
+/// The FEN string of the standard starting position.
+pub const STARTING_FEN: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+/// The number of files and of ranks on the board.
+pub const BOARD_SIZE: usize = 8;
+
+/// The highest full turn number a game can start on, so that the half
+/// turn number still fits in a `usize`.
+const MAX_FULL_TURN: usize = usize::MAX / 2 - 1;
+
+/// The highest count of half moves toward the fifty move rule a game can
+/// start with, so that counting on from it cannot overflow.
+const MAX_HALFMOVE_CLOCK: usize = usize::MAX / 2;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 /// A piece in chess
-struct Piece {
+pub struct Piece {
     piece: PieceType,
     color: Color,
 }
 
-#[derive(Clone, Copy, Debug)]
+impl Piece {
+    pub fn new(piece: PieceType, color: Color) -> Self {
+        Self { piece, color }
+    }
+
+    /// The type of the piece.
+    pub fn piece_type(&self) -> PieceType {
+        self.piece
+    }
+
+    /// The color of the piece.
+    pub fn color(&self) -> Color {
+        self.color
+    }
+
+    /// Reads a piece from its FEN letter, where uppercase is white and
+    /// lowercase is black.
+    pub fn from_fen_char(c: char) -> Option<Self> {
+        Some(Piece {
+            piece: PieceType::from_char(c)?,
+            color: if c.is_ascii_lowercase() {
+                Color::Black
+            } else {
+                Color::White
+            },
+        })
+    }
+
+    /// The FEN letter of the piece, uppercase for white and lowercase for
+    /// black.
+    pub fn to_fen_char(self) -> char {
+        match self.color {
+            Color::Black => self.piece.as_char().to_ascii_lowercase(),
+            Color::White => self.piece.as_char(),
+        }
+    }
+
+    /// The Unicode chess symbol of the piece, such as `♔` for a white king.
+    pub fn to_unicode(self) -> char {
+        match (self.color, self.piece) {
+            (Color::White, PieceType::King) => '♔',
+            (Color::White, PieceType::Queen) => '♕',
+            (Color::White, PieceType::Rook) => '♖',
+            (Color::White, PieceType::Bishop) => '♗',
+            (Color::White, PieceType::Knight) => '♘',
+            (Color::White, PieceType::Pawn) => '♙',
+            (Color::Black, PieceType::King) => '♚',
+            (Color::Black, PieceType::Queen) => '♛',
+            (Color::Black, PieceType::Rook) => '♜',
+            (Color::Black, PieceType::Bishop) => '♝',
+            (Color::Black, PieceType::Knight) => '♞',
+            (Color::Black, PieceType::Pawn) => '♟',
+        }
+    }
+}
+
+impl fmt::Display for Piece {
+    /// Writes the FEN letter of the piece.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.to_fen_char())
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 /// The type of piece in chess
-enum PieceType {
+pub enum PieceType {
     Pawn,
     Rook,
     Knight,
@@ -18,17 +135,369 @@ enum PieceType {
     King,
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 /// The color of the piece in chess
-enum Color {
+pub enum Color {
     Black,
     White,
 }
 
+impl PieceType {
+    /// Reads a piece type from its letter (`P`, `R`, `N`, `B`, `Q`, or
+    /// `K`) in either case.
+    pub fn from_char(c: char) -> Option<Self> {
+        match c.to_ascii_uppercase() {
+            'P' => Some(PieceType::Pawn),
+            'R' => Some(PieceType::Rook),
+            'N' => Some(PieceType::Knight),
+            'B' => Some(PieceType::Bishop),
+            'Q' => Some(PieceType::Queen),
+            'K' => Some(PieceType::King),
+            _ => None,
+        }
+    }
+
+    /// The usual value of the piece in centipawns: 100 for a pawn, 300
+    /// for a knight or bishop, 500 for a rook, and 900 for a queen. The
+    /// king cannot be traded, so it gets 10000, more than all of the other
+    /// pieces together.
+    pub fn value(self) -> i32 {
+        match self {
+            PieceType::Pawn => 100,
+            PieceType::Knight => 300,
+            PieceType::Bishop => 300,
+            PieceType::Rook => 500,
+            PieceType::Queen => 900,
+            PieceType::King => 10000,
+        }
+    }
+
+    /// The uppercase letter of the piece type, as used in SAN.
+    pub fn as_char(self) -> char {
+        match self {
+            PieceType::Pawn => 'P',
+            PieceType::Rook => 'R',
+            PieceType::Knight => 'N',
+            PieceType::Bishop => 'B',
+            PieceType::Queen => 'Q',
+            PieceType::King => 'K',
+        }
+    }
+
+    /// The lowercase English name of the piece type, like `"knight"`.
+    pub fn name(self) -> &'static str {
+        match self {
+            PieceType::Pawn => "pawn",
+            PieceType::Rook => "rook",
+            PieceType::Knight => "knight",
+            PieceType::Bishop => "bishop",
+            PieceType::Queen => "queen",
+            PieceType::King => "king",
+        }
+    }
+}
+
+impl FromStr for PieceType {
+    type Err = String;
+
+    /// Parses a single piece letter in either case.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut chars = s.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => Self::from_char(c),
+            _ => None,
+        }
+        .ok_or_else(|| format!("Invalid piece: {}", s))
+    }
+}
+
+impl Color {
+    /// Reads a color from the FEN turn letter, `w` or `b`.
+    pub fn from_fen_char(c: char) -> Option<Self> {
+        match c {
+            'w' => Some(Color::White),
+            'b' => Some(Color::Black),
+            _ => None,
+        }
+    }
+
+    /// The FEN turn letter of the color, `w` or `b`.
+    pub fn to_fen_char(self) -> char {
+        match self {
+            Color::White => 'w',
+            Color::Black => 'b',
+        }
+    }
+
+    /// The lowercase English name of the color, `"white"` or `"black"`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Color::White => "white",
+            Color::Black => "black",
+        }
+    }
+
+    /// The color of the other player.
+    pub fn opposite(self) -> Self {
+        match self {
+            Color::Black => Color::White,
+            Color::White => Color::Black,
+        }
+    }
+
+    /// The change in `y` of a pawn of this color moving forward. White
+    /// moves up the board, toward `y = 0` and the lower indices, and black
+    /// moves down it.
+    pub fn pawn_forward(self) -> i8 {
+        match self {
+            Color::White => -1,
+            Color::Black => 1,
+        }
+    }
+
+    /// [`Color::pawn_forward`] as an `i32`, to add to coordinates.
+    fn pawn_direction(self) -> i32 {
+        self.pawn_forward().into()
+    }
+
+    /// The `y` of the row this color's pawns start on, and can push two
+    /// squares from: 6 for white's 2nd rank and 1 for black's 7th.
+    pub fn pawn_start_rank(self) -> usize {
+        match self {
+            Color::White => 6,
+            Color::Black => 1,
+        }
+    }
+
+    /// The `y` of the row this color's pawns skip over when they push two
+    /// squares, where the en passant square is left.
+    fn en_passant_rank(self) -> usize {
+        (self.pawn_start_rank() as i32 + self.pawn_direction()) as usize
+    }
+
+    /// The `y` of the row this color's pawns promote on.
+    fn promotion_rank(self) -> usize {
+        self.opposite().back_rank()
+    }
+
+    /// The `y` of the row this color's pieces start on.
+    fn back_rank(self) -> usize {
+        match self {
+            Color::White => 7,
+            Color::Black => 0,
+        }
+    }
+}
+
+impl FromStr for Color {
+    type Err = String;
+
+    /// Parses `w`/`b` or `white`/`black`, ignoring case.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "w" | "white" => Ok(Color::White),
+            "b" | "black" => Ok(Color::Black),
+            _ => Err(format!("Invalid color: {}", s)),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+/// What kind of move a [`Move`] is. Combine flags with `|`.
+pub struct MoveFlags(u8);
+
+impl MoveFlags {
+    /// The move takes a piece, including en passant.
+    pub const CAPTURE: Self = Self(1);
+    /// The king castles.
+    pub const CASTLE: Self = Self(1 << 1);
+    /// A pawn takes another en passant.
+    pub const EN_PASSANT: Self = Self(1 << 2);
+    /// A pawn promotes.
+    pub const PROMOTION: Self = Self(1 << 3);
+    /// A pawn moves two squares forward.
+    pub const DOUBLE_PUSH: Self = Self(1 << 4);
+
+    /// No flags, as for a quiet move.
+    pub fn empty() -> Self {
+        Self(0)
+    }
+
+    /// Checks whether every flag in `other` is set.
+    pub fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+impl BitOr for MoveFlags {
+    type Output = Self;
+
+    fn bitor(self, other: Self) -> Self {
+        Self(self.0 | other.0)
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+/// A move from one square to another, given as indices in `0..64`
+/// (see [`Board::c_to_i`]). Castling is represented as the king moving
+/// two squares.
+///
+/// Moves are equal if they go between the same squares with the same
+/// promotion, whatever their flags.
+pub struct Move {
+    from: usize,
+    to: usize,
+    /// The piece a pawn promotes to when it reaches the last rank. A pawn
+    /// move onto the last rank without one is not legal.
+    promotion: Option<PieceType>,
+    /// What kind of move this is. Only moves from [`Game::legal_moves`]
+    /// have these filled in.
+    flags: MoveFlags,
+}
+
+impl PartialEq for Move {
+    fn eq(&self, other: &Self) -> bool {
+        (self.from, self.to, self.promotion) == (other.from, other.to, other.promotion)
+    }
+}
+
+impl Eq for Move {}
+
+impl Move {
+    pub fn new(from: usize, to: usize, promotion: Option<PieceType>) -> Self {
+        Self {
+            from,
+            to,
+            promotion,
+            flags: MoveFlags::empty(),
+        }
+    }
+
+    /// Sets the flags of a generated move.
+    fn with_flags(self, flags: MoveFlags) -> Self {
+        Self { flags, ..self }
+    }
+
+    /// Parses a move in UCI long algebraic notation, such as `"e2e4"` or
+    /// `"e7e8q"`.
+    pub fn from_uci(uci: &str) -> Result<Self, String> {
+        let invalid = || format!("Invalid UCI move: {}", uci);
+        if !uci.is_ascii() || !(4..=5).contains(&uci.len()) {
+            return Err(invalid());
+        }
+        let from = Board::a_to_i(&uci[0..2]).ok_or_else(invalid)?;
+        let to = Board::a_to_i(&uci[2..4]).ok_or_else(invalid)?;
+        let promotion = match uci.as_bytes().get(4) {
+            None => None,
+            Some(&c) if c.is_ascii_lowercase() => match PieceType::from_char(c as char) {
+                Some(PieceType::Pawn | PieceType::King) | None => return Err(invalid()),
+                promotion => promotion,
+            },
+            Some(_) => return Err(invalid()),
+        };
+        Ok(Self::new(from, to, promotion))
+    }
+
+    /// The index of the square the move starts from.
+    pub fn from(self) -> usize {
+        self.from
+    }
+
+    /// The index of the square the move ends on.
+    pub fn to(self) -> usize {
+        self.to
+    }
+
+    /// The piece a pawn promotes to, if any.
+    pub fn promotion(self) -> Option<PieceType> {
+        self.promotion
+    }
+
+    /// Checks whether a pawn promotes with this move.
+    pub fn is_promotion(self) -> bool {
+        self.promotion.is_some()
+    }
+
+    /// Checks whether the king castles with this move. Like
+    /// [`Move::flags`], this is only known for moves from
+    /// [`Game::legal_moves`].
+    pub fn is_castle(self) -> bool {
+        self.flags.contains(MoveFlags::CASTLE)
+    }
+
+    /// What kind of move this is, if it came from [`Game::legal_moves`].
+    /// Moves made with [`Move::new`] or parsed from text have no flags.
+    pub fn flags(self) -> MoveFlags {
+        self.flags
+    }
+
+    /// Packs the move into 16 bits: the square moved from in the low six,
+    /// the square moved to in the next six, and a four bit code for the
+    /// flags and promotion on top. Undo with [`Move::from_u16`].
+    pub fn to_u16(self) -> u16 {
+        let promotion = match self.promotion {
+            Some(PieceType::Knight) => 0b1000,
+            Some(PieceType::Bishop) => 0b1001,
+            Some(PieceType::Rook) => 0b1010,
+            Some(_) => 0b1011,
+            None => 0,
+        };
+        let code = if self.flags.contains(MoveFlags::EN_PASSANT) {
+            0b0101
+        } else if self.flags.contains(MoveFlags::CASTLE) {
+            0b0010
+        } else if self.flags.contains(MoveFlags::DOUBLE_PUSH) {
+            0b0001
+        } else if self.flags.contains(MoveFlags::CAPTURE) {
+            0b0100 | promotion
+        } else {
+            promotion
+        };
+        (self.from as u16 & 0x3f) | (self.to as u16 & 0x3f) << 6 | code << 12
+    }
+
+    /// Unpacks a move written by [`Move::to_u16`]. A promotion always comes
+    /// back with [`MoveFlags::PROMOTION`] set, even if it was not before.
+    pub fn from_u16(packed: u16) -> Self {
+        let code = packed >> 12;
+        let promotion = match code & 0b1011 {
+            0b1000 => Some(PieceType::Knight),
+            0b1001 => Some(PieceType::Bishop),
+            0b1010 => Some(PieceType::Rook),
+            0b1011 => Some(PieceType::Queen),
+            _ => None,
+        };
+        let flags = match code {
+            0b0001 => MoveFlags::DOUBLE_PUSH,
+            0b0010 => MoveFlags::CASTLE,
+            0b0100 => MoveFlags::CAPTURE,
+            0b0101 => MoveFlags::CAPTURE | MoveFlags::EN_PASSANT,
+            0b1000..=0b1011 => MoveFlags::PROMOTION,
+            0b1100..=0b1111 => MoveFlags::CAPTURE | MoveFlags::PROMOTION,
+            _ => MoveFlags::empty(),
+        };
+        Self::new(
+            (packed & 0x3f) as usize,
+            (packed >> 6 & 0x3f) as usize,
+            promotion,
+        )
+        .with_flags(flags)
+    }
+
+    /// Writes the move in UCI long algebraic notation.
+    pub fn to_uci(self) -> String {
+        let mut uci = Board::i_to_a(self.from) + &Board::i_to_a(self.to);
+        if let Some(promotion) = self.promotion {
+            uci.push(promotion.as_char().to_ascii_lowercase());
+        }
+        uci
+    }
+}
+
 #[derive(Clone, Debug)]
 /// Will contain the pieces on the board and the methods to interact
 /// with them
-struct Board {
+pub struct Board {
     /// Board will always be 8x8.
     ///
     /// Index 0 represents top-left of the board. Increasing index goes
@@ -37,19 +506,206 @@ struct Board {
     position: [Option<Piece>; 64],
 }
 
+// Everything indexing the board relies on this
+const _: () = assert!(Board::blank().position.len() == BOARD_SIZE * BOARD_SIZE);
+
 impl Board {
     /// Changes a coordinate (`x` in `0..8`, `y` in `0..8`) into an index
     /// in `0..64`.
     fn c_to_i(x: usize, y: usize) -> usize {
-        y * 8 + x
+        y * BOARD_SIZE + x
+    }
+
+    /// Changes an index in `0..64` back into a coordinate `(x, y)`.
+    fn i_to_c(index: usize) -> (usize, usize) {
+        (index % BOARD_SIZE, index / BOARD_SIZE)
     }
 
     /// Checks for a valid coordinate
     fn c_is_valid(x: usize, y: usize) -> bool {
-        (0..8).contains(&x) && (0..8).contains(&y)
+        (0..BOARD_SIZE).contains(&x) && (0..BOARD_SIZE).contains(&y)
+    }
+
+    /// The color of the square at a coordinate: `a1` is dark
+    /// ([`Color::Black`]) and `h1` is light ([`Color::White`]).
+    pub fn square_color(x: usize, y: usize) -> Color {
+        if (x + y).is_multiple_of(2) {
+            Color::White
+        } else {
+            Color::Black
+        }
+    }
+
+    /// Changes an algebraic square name such as `"e4"` into an index in
+    /// `0..64`.
+    fn a_to_i(square: &str) -> Option<usize> {
+        match square.as_bytes() {
+            [file @ b'a'..=b'h', rank @ b'1'..=b'8'] => {
+                Some(Self::c_to_i((file - b'a') as usize, (b'8' - rank) as usize))
+            }
+            _ => None,
+        }
+    }
+
+    /// Changes an index in `0..64` into an algebraic square name.
+    fn i_to_a(index: usize) -> String {
+        let (x, y) = Self::i_to_c(index);
+        format!("{}{}", (b'a' + x as u8) as char, BOARD_SIZE - y)
+    }
+
+    /// Moves a coordinate by `(dx, dy)`, returning `None` if that would
+    /// leave the board.
+    fn offset(x: usize, y: usize, dx: i32, dy: i32) -> Option<(usize, usize)> {
+        let (x, y) = (x as i32 + dx, y as i32 + dy);
+        let on_board = 0..BOARD_SIZE as i32;
+        if on_board.contains(&x) && on_board.contains(&y) {
+            Some((x as usize, y as usize))
+        } else {
+            None
+        }
+    }
+
+    /// The indices of the squares strictly between two squares on the
+    /// same rank, file, or diagonal, in order going from `a` to `b`. Empty
+    /// if they do not line up, are next to each other, or are not on the
+    /// board.
+    pub fn between(&self, a: usize, b: usize) -> Vec<usize> {
+        let size = BOARD_SIZE * BOARD_SIZE;
+        if a >= size || b >= size || a == b {
+            return vec![];
+        }
+        let ((ax, ay), (bx, by)) = (Self::i_to_c(a), Self::i_to_c(b));
+        let (dx, dy) = (bx as i32 - ax as i32, by as i32 - ay as i32);
+        if dx != 0 && dy != 0 && dx.abs() != dy.abs() {
+            return vec![];
+        }
+        let (dx, dy) = (dx.signum(), dy.signum());
+        let mut squares = vec![];
+        let mut square = Self::offset(ax, ay, dx, dy);
+        while let Some((x, y)) = square.filter(|&s| s != (bx, by)) {
+            squares.push(Self::c_to_i(x, y));
+            square = Self::offset(x, y, dx, dy);
+        }
+        squares
+    }
+
+    /// Checks whether a piece of the given type and color stands on
+    /// `(x, y)`.
+    fn has(&self, x: usize, y: usize, piece: PieceType, color: Color) -> bool {
+        matches!(self.get(x, y), Some(p) if p.piece == piece && p.color == color)
+    }
+
+    /// Gets the piece on a coordinate, if there is one.
+    pub fn get(&self, x: usize, y: usize) -> Option<Piece> {
+        if Self::c_is_valid(x, y) {
+            self.position[Self::c_to_i(x, y)]
+        } else {
+            None
+        }
+    }
+
+    /// Puts a piece on a coordinate, or clears it with [`None`], returning
+    /// whatever was there before. Coordinates off the board are left alone.
+    pub fn place(&mut self, x: usize, y: usize, piece: Option<Piece>) -> Option<Piece> {
+        if !Self::c_is_valid(x, y) {
+            return None;
+        }
+        std::mem::replace(&mut self.position[Self::c_to_i(x, y)], piece)
+    }
+
+    /// Puts a piece on a coordinate, or clears it with [`None`], like
+    /// [`Board::place`] but failing for coordinates off the board.
+    pub fn set_piece(&mut self, x: usize, y: usize, piece: Option<Piece>) -> Result<(), String> {
+        if !Self::c_is_valid(x, y) {
+            return Err(format!("Coordinate off the board: ({}, {})", x, y));
+        }
+        self.position[Self::c_to_i(x, y)] = piece;
+        Ok(())
+    }
+
+    /// Sets up a board with a piece on each coordinate in `map` and every
+    /// other square empty. Coordinates off the board are left out.
+    pub fn from_map(map: &HashMap<(usize, usize), Piece>) -> Self {
+        let mut board = Self::blank();
+        for (&(x, y), &piece) in map {
+            board.place(x, y, Some(piece));
+        }
+        board
+    }
+
+    /// Takes every piece off the board.
+    pub fn clear_all(&mut self) {
+        self.position = [None; 64];
+    }
+
+    /// Takes every piece off the board, the same as [`Board::clear_all`].
+    pub fn clear(&mut self) {
+        self.clear_all();
+    }
+
+    /// Changes every white piece to black and every black piece to white,
+    /// leaving them all on their squares. This does not mirror the board,
+    /// so the result is usually not the same position seen from the other
+    /// side: white's pawns on the 2nd rank become black pawns there, one
+    /// step from promoting.
+    pub fn swap_colors(&mut self) {
+        for piece in self.position.iter_mut().flatten() {
+            piece.color = piece.color.opposite();
+        }
+    }
+
+    /// Checks whether the coordinate is on the board and has no piece on
+    /// it.
+    pub fn is_empty(&self, x: usize, y: usize) -> bool {
+        Self::c_is_valid(x, y) && self.position[Self::c_to_i(x, y)].is_none()
+    }
+
+    /// Checks whether the coordinate is on the board and has a piece on
+    /// it.
+    pub fn is_occupied(&self, x: usize, y: usize) -> bool {
+        self.get(x, y).is_some()
     }
 
-    pub fn blank() -> Self {
+    /// A mask of the occupied squares, where bit `i` is set if there is a
+    /// piece on the square at index `i`.
+    pub fn occupied(&self) -> u64 {
+        self.mask(|_| true)
+    }
+
+    /// A mask of the squares holding a piece of `color`, laid out like
+    /// [`Board::occupied`].
+    pub fn occupied_by(&self, color: Color) -> u64 {
+        self.mask(|piece| piece.color == color)
+    }
+
+    /// Every piece on the board with the index of its square, from a8
+    /// across and down to h1. Iterating over `&Board` gives the same.
+    pub fn pieces(&self) -> Pieces<'_> {
+        Pieces {
+            squares: self.position.iter().enumerate(),
+        }
+    }
+
+    /// The coordinates of every square holding a piece of the given type
+    /// and color, from a8 across and down to h1.
+    pub fn find_pieces(&self, piece: PieceType, color: Color) -> Vec<(usize, usize)> {
+        self.pieces()
+            .filter(|(_, p)| p.piece == piece && p.color == color)
+            .map(|(index, _)| Self::i_to_c(index))
+            .collect()
+    }
+
+    /// A mask of the squares holding a piece that matches `filter`.
+    fn mask(&self, filter: impl Fn(Piece) -> bool) -> u64 {
+        self.position
+            .iter()
+            .enumerate()
+            .filter(|(_, piece)| piece.is_some_and(&filter))
+            .fold(0, |mask, (index, _)| mask | 1 << index)
+    }
+
+    /// An empty board. Same as [`Board::default`].
+    pub const fn blank() -> Self {
         Self {
             position: [None; 64],
         }
@@ -58,13 +714,15 @@ impl Board {
     /// Generate a board position from a Forsyth-Edwards Notation (FEN)
     /// standard string.
     /// Example string:
-    /// ```
+    /// ```text
     /// "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1"
     /// ```
+    /// Fields may be separated by any amount of whitespace, and whitespace
+    /// around the string, like a trailing newline, is ignored.
     pub fn from_fen(fen: &str) -> Result<Self, String> {
-        let mut position = [None; 64];
+        let mut position = [None; BOARD_SIZE * BOARD_SIZE];
         let (mut x, mut y) = (0, 0);
-        let mut fen: VecDeque<&str> = fen.split(' ').collect();
+        let mut fen: VecDeque<&str> = fen.split_whitespace().collect();
 
         let pos = if let Some(pos) = fen.pop_front() {
             pos
@@ -72,181 +730,848 @@ impl Board {
             return Err("No FEN?".to_owned());
         };
 
-        let mut last_invalid = false;
-
         for c in pos.bytes() {
-            // TODO: verify valid index
-            let index = Self::c_to_i(x, y);
-            let maybe_piece;
-            (maybe_piece, x, y) = match c.to_ascii_lowercase() {
-                b'r' => (
-                    Some(Piece {
-                        piece: PieceType::Rook,
-                        color: if c.is_ascii_lowercase() {
-                            Color::Black
-                        } else {
-                            Color::White
-                        },
-                    }),
-                    x + 1,
-                    y,
-                ),
-                b'n' => (
-                    Some(Piece {
-                        piece: PieceType::Knight,
-                        color: if c.is_ascii_lowercase() {
-                            Color::Black
-                        } else {
-                            Color::White
-                        },
-                    }),
-                    x + 1,
-                    y,
-                ),
-                b'b' => (
-                    Some(Piece {
-                        piece: PieceType::Bishop,
-                        color: if c.is_ascii_lowercase() {
-                            Color::Black
-                        } else {
-                            Color::White
-                        },
-                    }),
-                    x + 1,
-                    y,
-                ),
-                b'q' => (
-                    Some(Piece {
-                        piece: PieceType::Queen,
-                        color: if c.is_ascii_lowercase() {
-                            Color::Black
-                        } else {
-                            Color::White
-                        },
-                    }),
-                    x + 1,
-                    y,
-                ),
-                b'k' => (
-                    Some(Piece {
-                        piece: PieceType::King,
-                        color: if c.is_ascii_lowercase() {
-                            Color::Black
-                        } else {
-                            Color::White
-                        },
-                    }),
-                    x + 1,
-                    y,
-                ),
-                b'p' => (
-                    Some(Piece {
-                        piece: PieceType::Pawn,
-                        color: if c.is_ascii_lowercase() {
-                            Color::Black
-                        } else {
-                            Color::White
-                        },
-                    }),
-                    x + 1,
-                    y,
-                ),
-                b'/' => (None, 0, y + 1),
-                b'1'..=b'8' => (None, (c - b'1' + 1) as usize, y),
-                _ => return Err("Invalid char in FEN".to_owned()),
-            };
-            if let Some(_) = maybe_piece {
-                if last_invalid {
-                    return Err(format!("Invalid coordinate reached: {}, {}", x, y));
-                } else {
-                    position[index] = maybe_piece;
+            match c {
+                b'/' => (x, y) = (0, y + 1),
+                b'1'..=b'8' => x += (c - b'0') as usize,
+                _ => {
+                    let piece = Piece::from_fen_char(c as char).ok_or("Invalid char in FEN")?;
+                    // Too many squares in a rank or too many ranks run off
+                    // the board
+                    if !Self::c_is_valid(x, y) {
+                        return Err(format!("Invalid coordinate reached: {}, {}", x, y));
+                    }
+                    position[Self::c_to_i(x, y)] = Some(piece);
+                    x += 1;
                 }
             }
-            // Check new x and y for validity
-            last_invalid = if !Self::c_is_valid(x, y) {
-                true
-            } else {
-                false
-            };
         }
         Ok(Self { position })
     }
+
+    /// Generate the piece placement part of a Forsyth-Edwards Notation
+    /// (FEN) string.
+    pub fn to_fen(&self) -> String {
+        let mut fen = String::new();
+        for y in 0..BOARD_SIZE {
+            let mut empty = 0;
+            for x in 0..BOARD_SIZE {
+                let piece = match self.get(x, y) {
+                    Some(piece) => piece,
+                    None => {
+                        empty += 1;
+                        continue;
+                    }
+                };
+                if empty > 0 {
+                    fen.push_str(&empty.to_string());
+                    empty = 0;
+                }
+                fen.push(piece.to_fen_char());
+            }
+            if empty > 0 {
+                fen.push_str(&empty.to_string());
+            }
+            if y < BOARD_SIZE - 1 {
+                fen.push('/');
+            }
+        }
+        fen
+    }
+}
+
+impl Default for Board {
+    fn default() -> Self {
+        Self::blank()
+    }
+}
+
+impl<'a> IntoIterator for &'a Board {
+    type Item = (usize, Piece);
+    type IntoIter = Pieces<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.pieces()
+    }
+}
+
+/// An iterator over the pieces on a board, made by [`Board::pieces`].
+#[derive(Clone, Debug)]
+pub struct Pieces<'a> {
+    squares: std::iter::Enumerate<std::slice::Iter<'a, Option<Piece>>>,
 }
 
+impl Iterator for Pieces<'_> {
+    type Item = (usize, Piece);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.squares
+            .find_map(|(index, piece)| piece.map(|piece| (index, piece)))
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+/// How the fullmove number in a FEN exported by [`Game::to_fen_with`] is
+/// counted
+pub enum MoveNumbering {
+    /// Continue the numbering of the position the game started from.
+    Absolute,
+    /// Count from 1 at the position the game started from.
+    Relative,
+}
+
+#[derive(Clone, Debug)]
 /// Will contain the move history, position, next turn, etc.
-struct Game {
+pub struct Game {
     /// The board position.
     board: Board,
     /// The color of the next player.
     turn: Color,
-    /// The numbered full turn the game started on. Defaults to 1.
+    /// The numbered full turn the game started on. Defaults to 1. It is
+    /// set when the game is made and never changes, since undoing moves
+    /// stops at the starting position.
     start_turn: usize,
-    /// The numbered half turn that this turn represents. Starts at 1.
+    /// The numbered half turn that this turn represents. Starts at 1 for
+    /// white's first move, so it is one more than the number of plies
+    /// since the start of a game from the standard position.
     half_turn_num: usize,
     /// The numbered full turn that this turn represents. Starts at 1 and
     /// increments after black's turn.
     full_turn_num: usize,
-    /// A tracker for who can castle where. The [`char`]s must be in
-    /// `['K', 'k', 'Q', 'q']`.
-    castling: Vec<char>,
+    /// A tracker for who can castle where.
+    castling: CastlingRights,
+    /// The square a pawn skipped over with a double push on the last
+    /// turn, which can be captured onto en passant.
+    en_passant: Option<usize>,
     /// A tracker for the moves counting toward the fifty move rule
     fifty_move_rule: usize,
+    /// A hash of every position reached since the last pawn move, capture,
+    /// or loss of castling rights, including the current one, used to
+    /// detect repetitions.
+    position_history: Vec<u64>,
+    /// The PGN tag pairs the game was imported with.
+    headers: PgnHeaders,
+    /// The draw a player claimed, which ends the game.
+    draw_claim: Option<DrawClaim>,
+    /// The FEN of the position the game started from, used to replay the
+    /// game when exporting it.
+    start_fen: String,
+    /// Every move played since the start of the game.
+    moves: Vec<Move>,
+    /// What each move in `moves` changed, so it can be taken back.
+    undo_info: Vec<UndoInfo>,
+    /// The ply reached by the last pawn move, capture, or castle.
+    last_irreversible_ply: usize,
+    /// The Zobrist hash of the position, updated with each move.
+    hash: u64,
+    /// The material balance, updated with each move.
+    material: i32,
+    /// The ply each pass made with [`Game::make_null_move`] was made at,
+    /// and the en passant square it cleared.
+    null_moves: Vec<(usize, Option<usize>)>,
 }
 
 /// Generate a chess game from a Forsyth-Edwards Notation (FEN)
 /// standard string.
 /// Example string:
-/// ```
+/// ```text
 /// "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1"
 /// ```
 impl Game {
     pub fn from_fen(fen: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        // Anything the validator reports is an error here too, so a FEN
+        // loads exactly when Board::validate_fen finds nothing wrong
+        if let Some(error) = Board::validate_fen(fen).into_iter().next() {
+            return Err(error.into());
+        }
         let board = Board::from_fen(fen)?;
 
-        let mut fen: VecDeque<&str> = fen.split(' ').collect();
+        let mut fen: VecDeque<&str> = fen.split_whitespace().collect();
+
+        // The board position was already parsed above
+        fen.pop_front();
 
         // Whose turn it is to play
         let turn = match fen.pop_front() {
-            Some(turn) if turn != "b" && turn != "w" => return Err("Invalid turn specifier".into()),
-            Some(turn) if turn == "b" => Color::Black,
-            _ => Color::White,
+            Some(turn) => match turn.as_bytes() {
+                &[c] => Color::from_fen_char(c as char),
+                _ => None,
+            }
+            .ok_or("Invalid turn specifier")?,
+            None => Color::White,
         };
 
         let castling = match fen.pop_front() {
-            Some("-") => vec![],
-            Some(part) => {
-                let mut castling = vec![];
-                for c in part.chars() {
-                    if "KQkq".contains(c) {
-                        castling.push(c);
-                    } else {
-                        return Err(format!("Invalid char in castling part: {}", c).into());
-                    }
-                }
-                castling
-            }
-            _ => vec!['K', 'Q', 'k', 'q'],
+            Some(part) => part.parse::<CastlingRights>()?,
+            None => CastlingRights::ALL,
         };
 
-        // TODO: parse en passant square
-        let _en_passant_square = fen.pop_front();
+        // Only the row the last player's pawns skip over can hold the en
+        // passant square
+        let en_passant = match fen.pop_front() {
+            Some("-") | None => None,
+            Some(square) => match Board::a_to_i(square) {
+                Some(index) if index / BOARD_SIZE == turn.opposite().en_passant_rank() => {
+                    Some(index)
+                }
+                _ => return Err(format!("Invalid en passant square: {}", square).into()),
+            },
+        };
 
         let fifty_move_rule: usize = fen.pop_front().unwrap_or("0").parse()?;
+        if fifty_move_rule > MAX_HALFMOVE_CLOCK {
+            return Err(format!("Invalid halfmove clock: {}", fifty_move_rule).into());
+        }
 
         let full_turn_num: usize = fen.pop_front().unwrap_or("0").parse()?;
-        let half_turn_num = match turn {
-            Color::Black => full_turn_num * 2 + 1,
-            Color::White => full_turn_num * 2,
-        };
+        if full_turn_num > MAX_FULL_TURN {
+            return Err(format!("Invalid fullmove number: {}", full_turn_num).into());
+        }
+        Ok(Self::from_parts(
+            board,
+            turn,
+            castling,
+            en_passant,
+            fifty_move_rule,
+            full_turn_num,
+        ))
+    }
 
-        Ok(Self {
+    /// Starts a game from each part of a position, which must already
+    /// have been checked.
+    fn from_parts(
+        board: Board,
+        turn: Color,
+        castling: CastlingRights,
+        en_passant: Option<usize>,
+        fifty_move_rule: usize,
+        full_turn_num: usize,
+    ) -> Self {
+        // The plies played before this one in a game from the standard
+        // position, were it to have got here. Some FENs give the full turn
+        // as 0, which counts the same as 1.
+        let ply = full_turn_num.saturating_sub(1) * 2
+            + match turn {
+                Color::White => 0,
+                Color::Black => 1,
+            };
+
+        let mut game = Self {
             board,
             turn,
             start_turn: full_turn_num,
-            half_turn_num,
+            half_turn_num: ply + 1,
             full_turn_num,
             fifty_move_rule,
             castling,
-        })
+            en_passant,
+            position_history: vec![],
+            headers: PgnHeaders::default(),
+            draw_claim: None,
+            start_fen: String::new(),
+            moves: vec![],
+            undo_info: vec![],
+            last_irreversible_ply: 0,
+            hash: 0,
+            material: 0,
+            null_moves: vec![],
+        };
+        game.start_fen = game.to_fen();
+        game.hash = game.position_hash();
+        game.material = game.board.material_balance();
+        game.position_history.push(game.hash);
+        game
+    }
+
+    /// Plays a line of moves from the standard starting position. Each move
+    /// may be in UCI notation, like `"g1f3"`, or SAN, like `"Nf3"`.
+    pub fn from_moves(moves: &[&str]) -> Result<Self, String> {
+        let mut game = Self::from_fen(STARTING_FEN).map_err(|e| e.to_string())?;
+        for &text in moves {
+            let uci = Move::from_uci(text).ok();
+            let m = match game.legal_moves().into_iter().find(|&m| Some(m) == uci) {
+                Some(m) => m,
+                None => game.san_to_move(text)?,
+            };
+            game.make_legal_move(m);
+        }
+        Ok(game)
+    }
+
+    /// Loads a FEN and plays a line of UCI moves from it, as a UCI GUI
+    /// sends with `position fen <fen> moves e2e4 e7e5`.
+    pub fn from_fen_with_moves(fen: &str, moves: &[&str]) -> Result<Self, String> {
+        let mut game = Self::from_fen(fen).map_err(|e| e.to_string())?;
+        for uci in moves {
+            game.make_move_uci(uci)?;
+        }
+        Ok(game)
+    }
+
+    /// The board position.
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
+    /// The color of the next player.
+    pub fn turn(&self) -> Color {
+        self.turn
+    }
+
+    /// The numbered full turn the game started on.
+    pub fn start_turn(&self) -> usize {
+        self.start_turn
+    }
+
+    /// The numbered half turn that this turn represents: 1 for white's
+    /// first move, 2 for black's, and so on, worked out from the full turn
+    /// number when starting from a FEN.
+    pub fn half_turn_num(&self) -> usize {
+        self.half_turn_num
+    }
+
+    /// The numbered full turn that this turn represents.
+    pub fn full_turn_num(&self) -> usize {
+        self.full_turn_num
+    }
+
+    /// Who can still castle where.
+    pub fn castling_rights(&self) -> CastlingRights {
+        self.castling
+    }
+
+    /// The square that can be captured onto en passant, if any.
+    pub fn en_passant(&self) -> Option<usize> {
+        self.en_passant
+    }
+
+    /// The number of half moves since the last capture or pawn move.
+    pub fn fifty_move_rule(&self) -> usize {
+        self.fifty_move_rule
+    }
+
+    /// Generate a Forsyth-Edwards Notation (FEN) string for the game.
+    pub fn to_fen(&self) -> String {
+        self.to_fen_with(MoveNumbering::Absolute)
+    }
+
+    /// Generate a Forsyth-Edwards Notation (FEN) string for the game, with
+    /// the fullmove number counted as given by `numbering`.
+    pub fn to_fen_with(&self, numbering: MoveNumbering) -> String {
+        let en_passant = match self.en_passant {
+            Some(index) => Board::i_to_a(index),
+            None => "-".to_owned(),
+        };
+        format!(
+            "{} {} {} {} {} {}",
+            self.board.to_fen(),
+            self.turn.to_fen_char(),
+            self.castling,
+            en_passant,
+            self.fifty_move_rule,
+            match numbering {
+                MoveNumbering::Absolute => self.full_turn_num,
+                MoveNumbering::Relative => self.full_turn_num + 1 - self.start_turn,
+            }
+        )
+    }
+
+    /// The FEN of the position after playing `moves`, leaving the game
+    /// itself as it is. Fails if any of the moves is illegal.
+    pub fn fen_after(&self, moves: &[Move]) -> Result<String, String> {
+        let mut game = self.clone();
+        for &m in moves {
+            game.make_move(m)?;
+        }
+        Ok(game.to_fen())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_board_is_empty() {
+        let board = Board::default();
+        assert!(board.position.iter().all(Option::is_none));
+        assert_eq!(board.to_fen(), "8/8/8/8/8/8/8/8");
+    }
+
+    #[test]
+    fn castling_rights_are_normalized() {
+        let fen = |fen: &str| Game::from_fen(fen).map(|game| game.to_fen());
+        assert_eq!(
+            fen("r3k2r/8/8/8/8/8/8/R3K2R w kqKQ - 0 1").unwrap(),
+            "r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1"
+        );
+        assert_eq!(
+            fen("r3k2r/8/8/8/8/8/8/R3K2R w qK - 0 1").unwrap(),
+            "r3k2r/8/8/8/8/8/8/R3K2R w Kq - 0 1"
+        );
+        assert_eq!(
+            fen("r3k2r/8/8/8/8/8/8/R3K2R w - - 0 1").unwrap(),
+            "r3k2r/8/8/8/8/8/8/R3K2R w - - 0 1"
+        );
+        assert!(fen("r3k2r/8/8/8/8/8/8/R3K2R w KK - 0 1").is_err());
+        assert!(fen("r3k2r/8/8/8/8/8/8/R3K2R w KQx - 0 1").is_err());
+    }
+
+    #[test]
+    fn piece_and_color_letters_round_trip() {
+        for c in "PRNBQKprnbqk".chars() {
+            assert_eq!(Piece::from_fen_char(c).unwrap().to_fen_char(), c);
+            let piece_type = PieceType::from_char(c).unwrap();
+            assert_eq!(piece_type.as_char(), c.to_ascii_uppercase());
+            assert_eq!(c.to_string().parse::<PieceType>().unwrap(), piece_type);
+        }
+        assert!(PieceType::from_char('x').is_none());
+        assert!("".parse::<PieceType>().is_err());
+        assert!("NN".parse::<PieceType>().is_err());
+
+        assert_eq!(Color::from_fen_char('w'), Some(Color::White));
+        assert_eq!(Color::Black.to_fen_char(), 'b');
+        assert!(Color::from_fen_char('W').is_none());
+        for (text, color) in [
+            ("w", Color::White),
+            ("White", Color::White),
+            ("B", Color::Black),
+        ] {
+            assert_eq!(text.parse::<Color>().unwrap(), color);
+        }
+        assert!("red".parse::<Color>().is_err());
+    }
+
+    #[test]
+    fn square_colors() {
+        let color = |square| {
+            let (x, y) = Board::i_to_c(Board::a_to_i(square).unwrap());
+            Board::square_color(x, y)
+        };
+        assert_eq!(color("a1"), Color::Black);
+        assert_eq!(color("h1"), Color::White);
+        assert_eq!(color("a8"), Color::White);
+        assert_eq!(color("e4"), Color::White);
+        assert_eq!(color("d4"), Color::Black);
+    }
+
+    #[test]
+    fn piece_accessors_and_equality() {
+        let knight = Piece::new(PieceType::Knight, Color::White);
+        assert_eq!(knight.piece_type(), PieceType::Knight);
+        assert_eq!(knight.color(), Color::White);
+        assert_eq!(knight, Piece::new(PieceType::Knight, Color::White));
+        assert_ne!(knight, Piece::new(PieceType::Knight, Color::Black));
+        assert_ne!(knight, Piece::new(PieceType::Bishop, Color::White));
+    }
+
+    #[test]
+    fn fen_move_numbering() {
+        let fen = "r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 2 20";
+        let mut game = Game::from_fen(fen).unwrap();
+        assert_eq!(game.to_fen(), fen);
+        assert_eq!(game.to_fen_with(MoveNumbering::Absolute), fen);
+        assert!(game.to_fen_with(MoveNumbering::Relative).ends_with(" 2 1"));
+        game.make_move(Move::from_uci("f1b5").unwrap()).unwrap();
+        assert!(game.to_fen().ends_with(" 3 20"));
+        game.make_move(Move::from_uci("a7a6").unwrap()).unwrap();
+        assert!(game.to_fen().ends_with(" 0 21"));
+        assert!(game.to_fen_with(MoveNumbering::Relative).ends_with(" 0 2"));
+
+        // Starting with black to move, white's first move starts move 2
+        let mut game = Game::from_fen("4k3/8/8/8/8/8/8/R3K3 b - - 0 1").unwrap();
+        game.make_move(Move::from_uci("e8d8").unwrap()).unwrap();
+        assert!(game.to_fen_with(MoveNumbering::Relative).ends_with(" 2"));
+    }
+
+    #[test]
+    fn occupancy_masks() {
+        let board = Board::from_fen(STARTING_FEN).unwrap();
+        assert_eq!(board.occupied(), 0xFFFF_0000_0000_FFFF);
+        assert_eq!(board.occupied_by(Color::Black), 0xFFFF);
+        assert_eq!(board.occupied_by(Color::White), 0xFFFF_0000_0000_0000);
+        let board = Board::from_fen("k7/8/8/8/8/8/8/7K").unwrap();
+        assert_eq!(board.occupied(), 1 | 1 << 63);
+        assert_eq!(Board::blank().occupied(), 0);
+    }
+
+    #[test]
+    fn from_moves_accepts_san_and_uci() {
+        let game = Game::from_moves(&["e4", "e7e5", "Nf3", "b8c6", "Bb5"]).unwrap();
+        assert_eq!(
+            game.to_fen(),
+            "r1bqkbnr/pppp1ppp/2n5/1B2p3/4P3/5N2/PPPP1PPP/RNBQK2R b KQkq - 3 3"
+        );
+        assert_eq!(game.ply(), 5);
+        assert!(Game::from_moves(&["e4", "e4"]).is_err());
+        assert_eq!(Game::from_moves(&[]).unwrap().to_fen(), STARTING_FEN);
+    }
+
+    #[test]
+    fn pawn_helpers_per_color() {
+        let white = Color::White;
+        assert_eq!(white.pawn_direction(), -1);
+        assert_eq!(white.pawn_start_rank(), 6);
+        assert_eq!(white.promotion_rank(), 0);
+        assert_eq!(white.back_rank(), 7);
+        let black = Color::Black;
+        assert_eq!(black.pawn_direction(), 1);
+        assert_eq!(black.pawn_start_rank(), 1);
+        assert_eq!(black.promotion_rank(), 7);
+        assert_eq!(black.back_rank(), 0);
+    }
+
+    #[test]
+    fn generated_moves_carry_flags() {
+        let game = Game::from_fen("r3k2r/1P6/8/3pP3/8/8/8/R3K2R w KQkq d6 0 1").unwrap();
+        let flags = |uci: &str| {
+            let m = Move::from_uci(uci).unwrap();
+            game.legal_moves()
+                .into_iter()
+                .find(|&other| other == m)
+                .unwrap()
+                .flags()
+        };
+        assert!(flags("e1g1").contains(MoveFlags::CASTLE));
+        assert!(flags("e1c1").contains(MoveFlags::CASTLE));
+        assert!(flags("e5d6").contains(MoveFlags::EN_PASSANT | MoveFlags::CAPTURE));
+        assert!(flags("b7b8q").contains(MoveFlags::PROMOTION));
+        assert!(!flags("b7b8q").contains(MoveFlags::CAPTURE));
+        assert!(flags("b7a8n").contains(MoveFlags::PROMOTION | MoveFlags::CAPTURE));
+        assert!(flags("a1a8").contains(MoveFlags::CAPTURE));
+        assert_eq!(flags("e5e6"), MoveFlags::empty());
+
+        let game = Game::from_fen(STARTING_FEN).unwrap();
+        let push = Move::from_uci("e2e4").unwrap();
+        let generated = game.legal_moves().into_iter().find(|&m| m == push).unwrap();
+        assert!(generated.flags().contains(MoveFlags::DOUBLE_PUSH));
+        // Parsed moves have no flags until they are matched to a legal move
+        assert!(!push.flags().contains(MoveFlags::DOUBLE_PUSH));
+    }
+
+    #[test]
+    fn empty_and_occupied_squares() {
+        let board = Board::from_fen(STARTING_FEN).unwrap();
+        assert!(board.is_occupied(0, 0) && !board.is_empty(0, 0));
+        assert!(board.is_empty(4, 4) && !board.is_occupied(4, 4));
+        // Off the board is neither
+        assert!(!board.is_empty(8, 0) && !board.is_occupied(8, 0));
+        assert!(!board.is_empty(0, 8) && !board.is_occupied(0, 8));
+    }
+
+    #[test]
+    fn moves_round_trip_through_u16() {
+        for fen in [
+            STARTING_FEN,
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+            "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+        ] {
+            let game = Game::from_fen(fen).unwrap();
+            for m in game.legal_moves() {
+                let back = Move::from_u16(m.to_u16());
+                assert_eq!(back, m);
+                assert_eq!(back.flags(), m.flags(), "{}", m.to_uci());
+            }
+        }
+        let a1a8 = Move::from_uci("a1a8").unwrap();
+        assert_eq!(a1a8.to_u16(), 56);
+    }
+
+    #[test]
+    fn place_and_clear_all() {
+        let mut board = Board::blank();
+        let queen = Piece::new(PieceType::Queen, Color::White);
+        assert_eq!(board.place(3, 4, Some(queen)), None);
+        assert_eq!(board.get(3, 4), Some(queen));
+        assert_eq!(board.place(3, 4, None), Some(queen));
+        assert!(board.is_empty(3, 4));
+        assert_eq!(board.place(9, 4, Some(queen)), None);
+        assert_eq!(board.occupied(), 0);
+
+        let mut board = Board::from_fen(STARTING_FEN).unwrap();
+        board.clear_all();
+        assert_eq!(board.occupied(), 0);
+    }
+
+    #[test]
+    fn piece_and_color_names() {
+        assert_eq!(PieceType::Knight.name(), "knight");
+        assert_eq!(PieceType::King.name(), "king");
+        assert_eq!(Color::White.name(), "white");
+        assert_eq!(Color::Black.name(), "black");
+    }
+
+    #[test]
+    fn piece_values() {
+        assert_eq!(PieceType::Pawn.value(), 100);
+        assert_eq!(PieceType::Knight.value(), 300);
+        assert_eq!(PieceType::Bishop.value(), 300);
+        assert_eq!(PieceType::Rook.value(), 500);
+        assert_eq!(PieceType::Queen.value(), 900);
+        // The king is worth more than everything else together
+        let others = 8 * 100 + 2 * 300 + 2 * 300 + 2 * 500 + 900;
+        assert!(PieceType::King.value() > others);
+    }
+
+    #[test]
+    fn board_from_map() {
+        let king = Piece::new(PieceType::King, Color::White);
+        let mut map = HashMap::new();
+        map.insert((4, 7), king);
+        let board = Board::from_map(&map);
+        assert_eq!(board.get(4, 7), Some(king));
+        assert_eq!(board.occupied(), 1 << 60);
+        map.insert((8, 8), king);
+        assert_eq!(Board::from_map(&map).occupied(), 1 << 60);
+    }
+
+    #[test]
+    fn fen_after_moves() {
+        let game = Game::from_fen(STARTING_FEN).unwrap();
+        let moves: Vec<Move> = ["e2e4", "e7e5", "d1h5", "b8c6", "f1c4", "g8f6", "h5f7"]
+            .iter()
+            .map(|m| Move::from_uci(m).unwrap())
+            .collect();
+        assert_eq!(
+            game.fen_after(&moves).unwrap(),
+            "r1bqkb1r/pppp1Qpp/2n2n2/4p3/2B1P3/8/PPPP1PPP/RNB1K1NR b KQkq - 0 4"
+        );
+        assert_eq!(game.to_fen(), STARTING_FEN);
+        assert!(game.fen_after(&moves[..2]).is_ok());
+        assert!(game.fen_after(&[Move::from_uci("e2e5").unwrap()]).is_err());
+        assert_eq!(game.fen_after(&[]).unwrap(), STARTING_FEN);
+    }
+
+    #[test]
+    fn board_iteration() {
+        let game = Game::from_fen(STARTING_FEN).unwrap();
+        let mut count = 0;
+        for (index, piece) in game.board() {
+            assert_eq!(game.board().get(index % 8, index / 8), Some(piece));
+            count += 1;
+        }
+        assert_eq!(count, 32);
+        assert_eq!(game.board().pieces().count(), 32);
+        assert_eq!(
+            game.board().pieces().next(),
+            Some((0, Piece::new(PieceType::Rook, Color::Black)))
+        );
+        assert_eq!(Board::blank().pieces().count(), 0);
+    }
+
+    #[test]
+    fn find_pieces() {
+        let board = Game::from_fen(STARTING_FEN).unwrap().board().clone();
+        assert_eq!(
+            board.find_pieces(PieceType::Rook, Color::White),
+            vec![(0, 7), (7, 7)]
+        );
+        let pawns = board.find_pieces(PieceType::Pawn, Color::White);
+        assert_eq!(pawns.len(), 8);
+        assert!(pawns.iter().all(|&(_, y)| y == 6));
+        assert_eq!(
+            board.find_pieces(PieceType::King, Color::Black),
+            vec![(4, 0)]
+        );
+        assert!(Board::blank()
+            .find_pieces(PieceType::Queen, Color::White)
+            .is_empty());
+    }
+
+    #[test]
+    fn move_predicates() {
+        let game = Game::from_fen("r3k2r/1P6/8/8/8/8/8/R3K2R w KQkq - 0 1").unwrap();
+        let moves = game.legal_moves();
+        let find = |text: &str| *moves.iter().find(|m| m.to_uci() == text).unwrap();
+        assert!(find("e1g1").is_castle());
+        assert!(!find("e1g1").is_promotion());
+        assert!(!find("e1f1").is_castle());
+        assert!(find("b7b8q").is_promotion());
+        assert!(!Move::from_uci("e1g1").unwrap().is_castle());
+    }
+
+    #[test]
+    fn swap_colors() {
+        let game = Game::from_fen("r3k2r/1P6/8/3n4/8/8/5PPP/R3K2R w KQkq - 0 1").unwrap();
+        let mut board = game.board().clone();
+        board.swap_colors();
+        assert_eq!(board.to_fen(), "R3K2R/1p6/8/3N4/8/8/5ppp/r3k2r");
+        assert_eq!(board.occupied(), game.board().occupied());
+        board.swap_colors();
+        assert_eq!(board.to_fen(), game.board().to_fen());
+    }
+
+    #[test]
+    fn set_piece_and_clear() {
+        let mut board = Board::from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR").unwrap();
+        let knight = Piece::new(PieceType::Knight, Color::White);
+        board.set_piece(4, 4, Some(knight)).unwrap();
+        assert_eq!(board.get(4, 4), Some(knight));
+        board.set_piece(4, 6, None).unwrap();
+        assert_eq!(board.get(4, 6), None);
+        assert!(board.set_piece(8, 0, Some(knight)).is_err());
+        assert!(board.set_piece(0, 8, None).is_err());
+        board.clear();
+        assert_eq!(board.pieces().count(), 0);
+    }
+
+    #[test]
+    fn half_turn_numbers() {
+        let half = |fen: &str| Game::from_fen(fen).unwrap().half_turn_num();
+        assert_eq!(half(STARTING_FEN), 1);
+        assert_eq!(
+            half("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1"),
+            2
+        );
+        assert_eq!(
+            half("r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 2 3"),
+            5
+        );
+        assert_eq!(half("4k3/8/8/8/8/8/8/4K3 b - - 10 40"), 80);
+        assert_eq!(half("4k3/8/8/8/8/8/8/4K3 w - - 0 0"), 1);
+
+        let mut game = Game::from_fen(STARTING_FEN).unwrap();
+        game.make_move_san("e4").unwrap();
+        assert_eq!(game.half_turn_num(), 2);
+        game.make_move_san("e5").unwrap();
+        assert_eq!(game.half_turn_num(), 3);
+        assert_eq!(game.half_turn_num(), game.ply() + 1);
+    }
+
+    #[test]
+    fn fen_parsing_never_panics() {
+        let mut rng = zobrist::XorShift::new(1);
+        let mut random = move || rng.next();
+        let alphabet = b"pnbrqkPNBRQK12345678/ wb-KQkqabcdefgh0123456789x";
+        for round in 0..20_000 {
+            let len = (random() % 90) as usize;
+            let bytes: Vec<u8> = (0..len)
+                .map(|_| {
+                    let r = random();
+                    if round % 4 == 0 {
+                        r as u8
+                    } else {
+                        alphabet[r as usize % alphabet.len()]
+                    }
+                })
+                .collect();
+            let text = String::from_utf8_lossy(&bytes);
+            let _ = Board::from_fen(&text);
+            let _ = Game::from_fen(&text);
+
+            // Changing a few characters of a real FEN gets further before
+            // anything goes wrong
+            let mut fen: Vec<char> = STARTING_FEN.chars().collect();
+            for _ in 0..1 + random() % 4 {
+                let i = random() as usize % fen.len();
+                let c = alphabet[random() as usize % alphabet.len()] as char;
+                match random() % 3 {
+                    0 => fen[i] = c,
+                    1 => fen.insert(i, c),
+                    _ => {
+                        fen.remove(i);
+                    }
+                }
+                if fen.is_empty() {
+                    break;
+                }
+            }
+            let text: String = fen.into_iter().collect();
+            let _ = Board::from_fen(&text);
+            if let Ok(game) = Game::from_fen(&text) {
+                let _ = game.to_fen();
+                let _ = game.legal_moves();
+                let _ = game.status();
+            }
+        }
+    }
+
+    #[test]
+    fn fen_rejects_out_of_range_numbers() {
+        let huge = usize::MAX;
+        let clock = format!("4k3/8/8/8/8/8/8/4K2R w - - {} 1", huge);
+        assert!(Game::from_fen(&clock).is_err());
+        assert!(!Board::validate_fen(&clock).is_empty());
+        let number = format!("4k3/8/8/8/8/8/8/4K3 w - - 0 {}", huge);
+        assert!(Game::from_fen(&number).is_err());
+        assert!(!Board::validate_fen(&number).is_empty());
+
+        let mut game = Game::from_fen("4k3/8/8/8/8/8/8/4K2R w - - 149 80").unwrap();
+        game.make_move_uci("h1h2").unwrap();
+        assert_eq!(game.status(), GameStatus::SeventyFiveMoveRule);
+    }
+
+    #[test]
+    fn fen_rejects_pieces_off_the_board() {
+        assert!(Board::from_fen("8/8/8/8/8/8/8/8/k").is_err());
+        assert!(Board::from_fen("8k/8/8/8/8/8/8/8").is_err());
+        assert!(Board::from_fen("88888888k").is_err());
+        assert!(Board::from_fen("7k/8/8/8/8/8/8/7K").is_ok());
+    }
+
+    #[test]
+    fn fen_en_passant_rank_matches_turn() {
+        let fens = [
+            (
+                "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1",
+                true,
+            ),
+            (
+                "rnbqkbnr/ppp1pppp/8/3pP3/8/8/PPPP1PPP/RNBQKBNR w KQkq d6 0 2",
+                true,
+            ),
+            (
+                "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR w KQkq e3 0 1",
+                false,
+            ),
+            (
+                "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e4 0 1",
+                false,
+            ),
+            (
+                "rnbqkbnr/ppp1pppp/8/3pP3/8/8/PPPP1PPP/RNBQKBNR b KQkq d6 0 2",
+                false,
+            ),
+        ];
+        for (fen, valid) in fens {
+            assert_eq!(Game::from_fen(fen).is_ok(), valid, "{}", fen);
+            assert_eq!(Board::validate_fen(fen).is_empty(), valid, "{}", fen);
+        }
+    }
+
+    #[test]
+    fn from_fen_with_moves() {
+        let game = Game::from_fen_with_moves(STARTING_FEN, &["e2e4", "e7e5", "g1f3"]).unwrap();
+        assert_eq!(
+            game.to_fen(),
+            "rnbqkbnr/pppp1ppp/8/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R b KQkq - 1 2"
+        );
+        let game = Game::from_fen_with_moves("4k3/P7/8/8/8/8/8/4K3 w - - 0 1", &["a7a8q"]).unwrap();
+        assert_eq!(game.to_fen(), "Q3k3/8/8/8/8/8/8/4K3 b - - 0 1");
+        assert!(Game::from_fen_with_moves(STARTING_FEN, &["e2e5"]).is_err());
+        assert!(Game::from_fen_with_moves(STARTING_FEN, &["nonsense"]).is_err());
+        assert!(Game::from_fen_with_moves("bad", &[]).is_err());
+    }
+
+    #[test]
+    fn squares_between() {
+        let board = Board::blank();
+        let sq = |a: &str| Board::a_to_i(a).unwrap();
+        assert_eq!(
+            board.between(sq("a1"), sq("a5")),
+            vec![sq("a2"), sq("a3"), sq("a4")]
+        );
+        assert_eq!(
+            board.between(sq("a5"), sq("a1")),
+            vec![sq("a4"), sq("a3"), sq("a2")]
+        );
+        assert_eq!(board.between(sq("c1"), sq("f4")), vec![sq("d2"), sq("e3")]);
+        assert_eq!(board.between(sq("h1"), sq("a8")).len(), 6);
+        assert_eq!(board.between(sq("b1"), sq("h1")).len(), 5);
+        assert!(board.between(sq("a1"), sq("b3")).is_empty());
+        assert!(board.between(sq("a1"), sq("b2")).is_empty());
+        assert!(board.between(sq("a1"), sq("a1")).is_empty());
+        assert!(board.between(sq("a1"), 64).is_empty());
     }
 }
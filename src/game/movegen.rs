@@ -0,0 +1,1257 @@
+use super::{
+    zobrist, Board, CastlingRights, Color, Game, Move, MoveFlags, Piece, PieceType, UndoInfo,
+};
+
+/// Directions a knight can jump in.
+const KNIGHT_STEPS: [(i32, i32); 8] = [
+    (1, 2),
+    (2, 1),
+    (2, -1),
+    (1, -2),
+    (-1, -2),
+    (-2, -1),
+    (-2, 1),
+    (-1, 2),
+];
+
+/// Directions a king can step in, and a queen can slide in.
+pub(super) const KING_STEPS: [(i32, i32); 8] = [
+    (0, -1),
+    (1, -1),
+    (1, 0),
+    (1, 1),
+    (0, 1),
+    (-1, 1),
+    (-1, 0),
+    (-1, -1),
+];
+
+/// Directions a rook can slide in.
+const ROOK_DIRECTIONS: [(i32, i32); 4] = [(0, -1), (1, 0), (0, 1), (-1, 0)];
+
+/// Directions a bishop can slide in.
+const BISHOP_DIRECTIONS: [(i32, i32); 4] = [(1, -1), (1, 1), (-1, 1), (-1, -1)];
+
+/// The pieces a pawn may promote to, from most to least valuable.
+const PROMOTION_PIECES: [PieceType; 4] = [
+    PieceType::Queen,
+    PieceType::Rook,
+    PieceType::Bishop,
+    PieceType::Knight,
+];
+
+impl Board {
+    /// Finds the coordinate of the king of the given color.
+    pub fn king(&self, color: Color) -> Option<(usize, usize)> {
+        self.position
+            .iter()
+            .position(|p| matches!(p, Some(p) if p.piece == PieceType::King && p.color == color))
+            .map(Self::i_to_c)
+    }
+
+    /// Checks whether any piece of color `by` attacks the coordinate
+    /// `(x, y)`, regardless of what stands on it.
+    pub fn is_attacked(&self, x: usize, y: usize, by: Color) -> bool {
+        // A pawn attacks diagonally forward, so look one row behind it
+        let pawn_dy = -by.pawn_direction();
+        for dx in [-1, 1] {
+            if let Some((px, py)) = Self::offset(x, y, dx, pawn_dy) {
+                if self.has(px, py, PieceType::Pawn, by) {
+                    return true;
+                }
+            }
+        }
+
+        for (dx, dy) in KNIGHT_STEPS {
+            if let Some((nx, ny)) = Self::offset(x, y, dx, dy) {
+                if self.has(nx, ny, PieceType::Knight, by) {
+                    return true;
+                }
+            }
+        }
+
+        for (dx, dy) in KING_STEPS {
+            if let Some((kx, ky)) = Self::offset(x, y, dx, dy) {
+                if self.has(kx, ky, PieceType::King, by) {
+                    return true;
+                }
+            }
+        }
+
+        let sliders = ROOK_DIRECTIONS
+            .iter()
+            .map(|d| (d, PieceType::Rook))
+            .chain(BISHOP_DIRECTIONS.iter().map(|d| (d, PieceType::Bishop)));
+        for (&(dx, dy), slider) in sliders {
+            let (mut sx, mut sy) = (x, y);
+            while let Some((nx, ny)) = Self::offset(sx, sy, dx, dy) {
+                if let Some(piece) = self.get(nx, ny) {
+                    if piece.color == by
+                        && (piece.piece == slider || piece.piece == PieceType::Queen)
+                    {
+                        return true;
+                    }
+                    break;
+                }
+                (sx, sy) = (nx, ny);
+            }
+        }
+
+        false
+    }
+
+    /// Moves the pieces for a generated move, including the rook of a
+    /// castle and a pawn taken en passant, leaving everything else about
+    /// the game alone. This is all that is needed to see what a move
+    /// attacks or leaves attacked.
+    fn play(&mut self, m: Move) {
+        let piece = match self.position[m.from].take() {
+            Some(piece) => piece,
+            None => return,
+        };
+        let (from_x, from_y) = Self::i_to_c(m.from);
+        let (to_x, _) = Self::i_to_c(m.to);
+        if m.flags.contains(MoveFlags::EN_PASSANT) {
+            self.position[Self::c_to_i(to_x, from_y)] = None;
+        } else if m.is_castle() {
+            let (rook_from, rook_to) = if to_x > from_x { (7, 5) } else { (0, 3) };
+            let rook = self.position[Self::c_to_i(rook_from, from_y)].take();
+            self.position[Self::c_to_i(rook_to, from_y)] = rook;
+        }
+        self.position[m.to] = Some(match m.promotion {
+            Some(promotion) => Piece {
+                piece: promotion,
+                color: piece.color,
+            },
+            None => piece,
+        });
+    }
+
+    /// The pieces of `color` pinned to their king, by index, each with the
+    /// step `(dx, dy)` from the piece toward the king. A pinned piece can
+    /// only move along that line. `y` grows toward white's side, as for
+    /// coordinates, so a piece pinned from above has a `dy` of 1.
+    pub fn pinned_pieces(&self, color: Color) -> Vec<(usize, (i8, i8))> {
+        let (king_x, king_y) = match self.king(color) {
+            Some(king) => king,
+            None => return vec![],
+        };
+        let sliders = ROOK_DIRECTIONS
+            .iter()
+            .map(|d| (d, PieceType::Rook))
+            .chain(BISHOP_DIRECTIONS.iter().map(|d| (d, PieceType::Bishop)));
+        let mut pinned = vec![];
+        for (&(dx, dy), slider) in sliders {
+            // The first piece out from the king is pinned if it is our own
+            // and the next one is an enemy sliding along the same line
+            let mut own = None;
+            let mut square = Self::offset(king_x, king_y, dx, dy);
+            while let Some((x, y)) = square {
+                if let Some(piece) = self.get(x, y) {
+                    match own {
+                        None if piece.color == color => own = Some(Self::c_to_i(x, y)),
+                        None => break,
+                        Some(index) => {
+                            if piece.color != color
+                                && (piece.piece == slider || piece.piece == PieceType::Queen)
+                            {
+                                pinned.push((index, (-dx as i8, -dy as i8)));
+                            }
+                            break;
+                        }
+                    }
+                }
+                square = Self::offset(x, y, dx, dy);
+            }
+        }
+        pinned.sort_unstable();
+        pinned
+    }
+
+    /// Marks every square attacked by a piece of color `by`, by index.
+    pub fn attack_map(&self, by: Color) -> [bool; 64] {
+        let mut map = [false; 64];
+        for (index, attacked) in map.iter_mut().enumerate() {
+            let (x, y) = Self::i_to_c(index);
+            *attacked = self.is_attacked(x, y, by);
+        }
+        map
+    }
+
+    /// The coordinates of every square attacked by a piece of color `by`,
+    /// each given once, from a8 across and down to h1.
+    pub fn attacked_squares(&self, by: Color) -> impl Iterator<Item = (usize, usize)> + '_ {
+        (0..64)
+            .map(Self::i_to_c)
+            .filter(move |&(x, y)| self.is_attacked(x, y, by))
+    }
+
+    /// Checks whether a piece on `(x, y)` would be recaptured by `color`
+    /// if it were taken, meaning some piece of `color` attacks the square.
+    /// A piece that nothing of its own color defends is hanging. Pins are
+    /// not considered, and neither is whether a king could recapture
+    /// without moving into check.
+    pub fn is_defended(&self, x: usize, y: usize, color: Color) -> bool {
+        self.is_attacked(x, y, color)
+    }
+
+    /// The indices of the enemy pieces giving check to the king of
+    /// `color`. Unlike [`Board::attackers_of`], sliders behind another
+    /// attacker are left out, since they do not reach the king yet. Two
+    /// checkers make a double check, which only a king move gets out of.
+    pub fn checkers(&self, color: Color) -> Vec<usize> {
+        let (x, y) = match self.king(color) {
+            Some(king) => king,
+            None => return vec![],
+        };
+        let king = Self::c_to_i(x, y);
+        // Knights, pawns, and kings never have anything in between
+        self.attackers_of(x, y, color.opposite())
+            .into_iter()
+            .map(|(ax, ay)| Self::c_to_i(ax, ay))
+            .filter(|&attacker| {
+                self.between(king, attacker)
+                    .into_iter()
+                    .all(|index| self.position[index].is_none())
+            })
+            .collect()
+    }
+
+    /// Lists the coordinates of every piece of `color` that attacks
+    /// `(x, y)`. Sliders lined up behind another attacker on the same
+    /// line (a battery) are included, since they join in once the piece
+    /// in front has captured.
+    pub fn attackers_of(&self, x: usize, y: usize, color: Color) -> Vec<(usize, usize)> {
+        let mut attackers = Vec::new();
+
+        for (dx, dy) in KNIGHT_STEPS {
+            if let Some((nx, ny)) = Self::offset(x, y, dx, dy) {
+                if self.has(nx, ny, PieceType::Knight, color) {
+                    attackers.push((nx, ny));
+                }
+            }
+        }
+
+        let pawn_dy = -color.pawn_direction();
+        for (dx, dy) in KING_STEPS {
+            let diagonal = dx != 0 && dy != 0;
+            let (mut sx, mut sy) = (x, y);
+            let mut distance = 0;
+            while let Some((nx, ny)) = Self::offset(sx, sy, dx, dy) {
+                (sx, sy) = (nx, ny);
+                distance += 1;
+                let piece = match self.get(nx, ny) {
+                    Some(piece) => piece,
+                    None => continue,
+                };
+                let attacks = piece.color == color
+                    && match piece.piece {
+                        PieceType::Queen => true,
+                        PieceType::Rook => !diagonal,
+                        PieceType::Bishop => diagonal,
+                        // Pawns and kings only reach adjacent squares, so
+                        // they can only be at the front of a battery
+                        PieceType::Pawn => distance == 1 && diagonal && dy == pawn_dy,
+                        PieceType::King => distance == 1,
+                        PieceType::Knight => false,
+                    };
+                if !attacks {
+                    break;
+                }
+                attackers.push((nx, ny));
+            }
+        }
+
+        attackers
+    }
+
+    /// Counts the squares the knights, bishops, rooks, and queens of
+    /// `color` could move to, ignoring pins and checks. Each square is
+    /// weighted by the piece, so a knight finding one more square counts
+    /// for more than a queen doing the same.
+    pub fn mobility(&self, color: Color) -> i32 {
+        let mut mobility = 0;
+        for (i, piece) in self.position.iter().enumerate() {
+            let piece = match piece {
+                Some(piece) if piece.color == color => piece,
+                _ => continue,
+            };
+            let (x, y) = Self::i_to_c(i);
+            let (directions, slides, weight): (&[(i32, i32)], bool, i32) = match piece.piece {
+                PieceType::Knight => (&KNIGHT_STEPS, false, 4),
+                PieceType::Bishop => (&BISHOP_DIRECTIONS, true, 4),
+                PieceType::Rook => (&ROOK_DIRECTIONS, true, 2),
+                PieceType::Queen => (&KING_STEPS, true, 1),
+                PieceType::Pawn | PieceType::King => continue,
+            };
+            for &(dx, dy) in directions {
+                let (mut sx, mut sy) = (x, y);
+                while let Some((nx, ny)) = Self::offset(sx, sy, dx, dy) {
+                    match self.get(nx, ny) {
+                        Some(other) => {
+                            if other.color != color {
+                                mobility += weight;
+                            }
+                            break;
+                        }
+                        None => mobility += weight,
+                    }
+                    if !slides {
+                        break;
+                    }
+                    (sx, sy) = (nx, ny);
+                }
+            }
+        }
+        mobility
+    }
+}
+
+impl Game {
+    /// Checks whether the player to move is in check.
+    pub fn is_in_check(&self) -> bool {
+        match self.board.king(self.turn) {
+            Some((x, y)) => self.board.is_attacked(x, y, self.turn.opposite()),
+            None => false,
+        }
+    }
+
+    /// Generates every move for the player to move, including ones that
+    /// would leave their own king in check.
+    fn pseudo_legal_moves(&self) -> Vec<Move> {
+        let mut moves = Vec::new();
+        for from in 0..64 {
+            match self.board.position[from] {
+                Some(piece) if piece.color == self.turn => {
+                    self.piece_moves(from, piece, &mut moves)
+                }
+                _ => {}
+            }
+        }
+        moves
+    }
+
+    /// Adds the pseudo-legal moves of the piece on `from` to `moves`.
+    fn piece_moves(&self, from: usize, piece: Piece, moves: &mut Vec<Move>) {
+        let (x, y) = Board::i_to_c(from);
+        match piece.piece {
+            PieceType::Pawn => self.pawn_moves(x, y, piece.color, moves),
+            PieceType::Knight => self.step_moves(x, y, piece.color, &KNIGHT_STEPS, moves),
+            PieceType::Bishop => self.slide_moves(x, y, piece.color, &BISHOP_DIRECTIONS, moves),
+            PieceType::Rook => self.slide_moves(x, y, piece.color, &ROOK_DIRECTIONS, moves),
+            PieceType::Queen => self.slide_moves(x, y, piece.color, &KING_STEPS, moves),
+            PieceType::King => {
+                self.step_moves(x, y, piece.color, &KING_STEPS, moves);
+                self.castling_moves(x, y, piece.color, moves);
+            }
+        }
+    }
+
+    /// Adds moves for pieces that jump a single step in each direction.
+    fn step_moves(
+        &self,
+        x: usize,
+        y: usize,
+        color: Color,
+        steps: &[(i32, i32)],
+        moves: &mut Vec<Move>,
+    ) {
+        let from = Board::c_to_i(x, y);
+        for &(dx, dy) in steps {
+            if let Some((tx, ty)) = Board::offset(x, y, dx, dy) {
+                let flags = match self.board.get(tx, ty) {
+                    Some(piece) if piece.color == color => continue,
+                    Some(_) => MoveFlags::CAPTURE,
+                    None => MoveFlags::empty(),
+                };
+                moves.push(Move::new(from, Board::c_to_i(tx, ty), None).with_flags(flags));
+            }
+        }
+    }
+
+    /// Adds moves for pieces that slide until they hit another piece.
+    fn slide_moves(
+        &self,
+        x: usize,
+        y: usize,
+        color: Color,
+        directions: &[(i32, i32)],
+        moves: &mut Vec<Move>,
+    ) {
+        let from = Board::c_to_i(x, y);
+        for &(dx, dy) in directions {
+            let (mut sx, mut sy) = (x, y);
+            while let Some((tx, ty)) = Board::offset(sx, sy, dx, dy) {
+                match self.board.get(tx, ty) {
+                    Some(piece) => {
+                        if piece.color != color {
+                            let m = Move::new(from, Board::c_to_i(tx, ty), None);
+                            moves.push(m.with_flags(MoveFlags::CAPTURE));
+                        }
+                        break;
+                    }
+                    None => moves.push(Move::new(from, Board::c_to_i(tx, ty), None)),
+                }
+                (sx, sy) = (tx, ty);
+            }
+        }
+    }
+
+    /// Adds pawn pushes and captures. Every push or capture onto the last
+    /// rank is expanded into one move per promotion piece, so a pawn that
+    /// can both push and capture there has eight moves.
+    fn pawn_moves(&self, x: usize, y: usize, color: Color, moves: &mut Vec<Move>) {
+        let from = Board::c_to_i(x, y);
+        let (dy, start_row, last_row) = (
+            color.pawn_direction(),
+            color.pawn_start_rank(),
+            color.promotion_rank(),
+        );
+        let mut push = |tx: usize, ty: usize, flags: MoveFlags| {
+            let to = Board::c_to_i(tx, ty);
+            if ty == last_row {
+                for piece in PROMOTION_PIECES {
+                    let m = Move::new(from, to, Some(piece));
+                    moves.push(m.with_flags(flags | MoveFlags::PROMOTION));
+                }
+            } else {
+                moves.push(Move::new(from, to, None).with_flags(flags));
+            }
+        };
+
+        if let Some((tx, ty)) = Board::offset(x, y, 0, dy) {
+            if self.board.is_empty(tx, ty) {
+                push(tx, ty, MoveFlags::empty());
+                if y == start_row {
+                    if let Some((tx, ty)) = Board::offset(x, y, 0, 2 * dy) {
+                        if self.board.is_empty(tx, ty) {
+                            push(tx, ty, MoveFlags::DOUBLE_PUSH);
+                        }
+                    }
+                }
+            }
+        }
+
+        for dx in [-1, 1] {
+            if let Some((tx, ty)) = Board::offset(x, y, dx, dy) {
+                let flags = match self.board.get(tx, ty) {
+                    Some(piece) if piece.color != color => MoveFlags::CAPTURE,
+                    None if self.en_passant == Some(Board::c_to_i(tx, ty)) => {
+                        MoveFlags::CAPTURE | MoveFlags::EN_PASSANT
+                    }
+                    _ => continue,
+                };
+                push(tx, ty, flags);
+            }
+        }
+    }
+
+    /// Adds castling moves for a king on `(x, y)` if the rights remain,
+    /// the squares between king and rook are empty, and the king does not
+    /// start in, pass through, or land on an attacked square.
+    fn castling_moves(&self, x: usize, y: usize, color: Color, moves: &mut Vec<Move>) {
+        let from = Board::c_to_i(x, y);
+        for (kingside, to_x) in [(true, 6), (false, 2)] {
+            if self.can_castle(color, kingside) {
+                let m = Move::new(from, Board::c_to_i(to_x, y), None);
+                moves.push(m.with_flags(MoveFlags::CASTLE));
+            }
+        }
+    }
+
+    /// Checks whether `color` could castle kingside right now: it still
+    /// has the right, the squares between the king and rook are empty, and
+    /// the king is not in check and does not cross or land on an attacked
+    /// square. Whose turn it is does not matter.
+    pub fn can_castle_kingside(&self, color: Color) -> bool {
+        self.can_castle(color, true)
+    }
+
+    /// Checks whether `color` could castle queenside right now, like
+    /// [`Game::can_castle_kingside`].
+    pub fn can_castle_queenside(&self, color: Color) -> bool {
+        self.can_castle(color, false)
+    }
+
+    /// Checks whether `color` could castle on the given side right now.
+    fn can_castle(&self, color: Color, kingside: bool) -> bool {
+        let home_row = color.back_rank();
+        // (rook file, squares that must be empty, squares the king crosses)
+        let (rook_x, empty, path): (usize, &[usize], [usize; 2]) = if kingside {
+            (7, &[5, 6], [5, 6])
+        } else {
+            (0, &[1, 2, 3], [3, 2])
+        };
+        let enemy = color.opposite();
+        self.castling.contains(CastlingRights::of(color, kingside))
+            && self.board.has(4, home_row, PieceType::King, color)
+            && self.board.has(rook_x, home_row, PieceType::Rook, color)
+            && !self.board.is_attacked(4, home_row, enemy)
+            && empty.iter().all(|&ex| self.board.is_empty(ex, home_row))
+            && path
+                .iter()
+                .all(|&px| !self.board.is_attacked(px, home_row, enemy))
+    }
+
+    /// Generates every legal move for the player to move, sorted by the
+    /// square moved from, then the square moved to, with squares ordered
+    /// a1, b1, ..., h1, a2, ..., h8. Promotions to the same square come in
+    /// the order queen, rook, bishop, knight.
+    pub fn legal_moves(&self) -> Vec<Move> {
+        // In double check only the king can move, so nothing else is
+        // worth generating
+        if self.board.checkers(self.turn).len() >= 2 {
+            return match self.board.king(self.turn) {
+                Some((x, y)) => self.legal_moves_for(Board::c_to_i(x, y)),
+                None => vec![],
+            };
+        }
+        let mut moves: Vec<Move> = self
+            .pseudo_legal_moves()
+            .into_iter()
+            .filter(|&m| self.is_legal_pseudo(m))
+            .collect();
+        Self::sort_moves(&mut moves);
+        moves
+    }
+
+    /// Generates the legal moves that match `pred`, in the same order as
+    /// [`Game::legal_moves`]. Moves are checked against `pred` before they
+    /// are checked for legality, so a narrow filter also saves playing out
+    /// the moves it rejects.
+    pub fn legal_moves_where(&self, pred: impl Fn(&Move) -> bool) -> Vec<Move> {
+        let mut moves: Vec<Move> = self
+            .pseudo_legal_moves()
+            .into_iter()
+            .filter(|m| pred(m) && self.is_legal_pseudo(*m))
+            .collect();
+        Self::sort_moves(&mut moves);
+        moves
+    }
+
+    /// Generates the legal moves that capture, including en passant, or
+    /// promote, in the same order as [`Game::legal_moves`]. These are the
+    /// moves a quiescence search keeps looking at past its depth.
+    pub fn capture_moves(&self) -> Vec<Move> {
+        self.legal_moves_where(|m| m.flags.contains(MoveFlags::CAPTURE) || m.is_promotion())
+    }
+
+    /// Generates the legal moves that get the player to move out of check,
+    /// in the same order as [`Game::legal_moves`]: moving the king,
+    /// capturing the checking piece, or blocking its line to the king. In
+    /// double check only the king can move. There are none if the player is
+    /// not in check.
+    ///
+    /// Only moves to those squares are tried, so this is quicker than
+    /// filtering every move.
+    pub fn moves_resolving_check(&self) -> Vec<Move> {
+        let (king_x, king_y) = match self.board.king(self.turn) {
+            Some(king) => king,
+            None => return vec![],
+        };
+        let checkers = self.board.checkers(self.turn);
+        if checkers.is_empty() {
+            return vec![];
+        }
+
+        let king = Board::c_to_i(king_x, king_y);
+        // Squares a piece other than the king can move to in reply
+        let mut targets = vec![];
+        if let [checker] = checkers[..] {
+            // A knight never lines up with the king, so it cannot be
+            // blocked
+            targets = self.board.between(king, checker);
+            targets.push(checker);
+        }
+
+        let mut moves = Vec::new();
+        for from in 0..64 {
+            match self.board.position[from] {
+                Some(piece) if piece.color == self.turn => {
+                    self.piece_moves(from, piece, &mut moves)
+                }
+                _ => {}
+            }
+        }
+        moves.retain(|&m| {
+            // An en passant capture takes the checking pawn from beside
+            // the target square
+            let captured = if m.flags.contains(MoveFlags::EN_PASSANT) {
+                Board::c_to_i(m.to % 8, m.from / 8)
+            } else {
+                m.to
+            };
+            (m.from == king || targets.contains(&m.to) || targets.contains(&captured))
+                && self.is_legal_pseudo(m)
+        });
+        Self::sort_moves(&mut moves);
+        moves
+    }
+
+    /// Generates the legal moves of the piece on the square at index
+    /// `from`, in the same order as [`Game::legal_moves`]. There are none
+    /// if the square is empty or holds a piece of the player not to move.
+    pub fn legal_moves_for(&self, from: usize) -> Vec<Move> {
+        let mut moves = Vec::new();
+        match self.board.position.get(from).copied().flatten() {
+            Some(piece) if piece.color == self.turn => self.piece_moves(from, piece, &mut moves),
+            _ => return moves,
+        }
+        moves.retain(|&m| self.is_legal_pseudo(m));
+        Self::sort_moves(&mut moves);
+        moves
+    }
+
+    /// Sorts moves into the order documented on [`Game::legal_moves`].
+    fn sort_moves(moves: &mut [Move]) {
+        // Count ranks up from white's side, unlike board indices. A stable
+        // sort keeps promotions in the order they were generated.
+        let square = |index: usize| (7 - index / 8) * 8 + index % 8;
+        moves.sort_by_key(|m| (square(m.from), square(m.to)));
+    }
+
+    /// Checks whether the player to move has any legal move, stopping at
+    /// the first one found.
+    pub fn has_legal_move(&self) -> bool {
+        let mut moves = Vec::new();
+        for from in 0..64 {
+            match self.board.position[from] {
+                Some(piece) if piece.color == self.turn => {
+                    moves.clear();
+                    self.piece_moves(from, piece, &mut moves);
+                    if moves.iter().any(|&m| self.is_legal_pseudo(m)) {
+                        return true;
+                    }
+                }
+                _ => {}
+            }
+        }
+        false
+    }
+
+    /// Checks that a pseudo-legal move does not leave the mover's own king
+    /// in check. The move is played out on a copy of the board first, so
+    /// an en passant capture that takes both pawns off the king's rank and
+    /// exposes it to a rook or queen is caught as well.
+    fn is_legal_pseudo(&self, m: Move) -> bool {
+        let mut board = self.board.clone();
+        board.play(m);
+        match board.king(self.turn) {
+            Some((x, y)) => !board.is_attacked(x, y, self.turn.opposite()),
+            None => true,
+        }
+    }
+
+    /// Plays a move if it is legal for the player to move. Once the game
+    /// is over, by checkmate, a draw, or a claimed draw, no more moves can
+    /// be made.
+    pub fn make_move(&mut self, m: Move) -> Result<(), String> {
+        if let Some(reason) = self.status_reason() {
+            return Err(format!("The game is over. {}", reason));
+        }
+        let m = self
+            .generated(m)
+            .ok_or_else(|| format!("Illegal move: {}", m.to_uci()))?;
+        self.make_legal_move(m);
+        Ok(())
+    }
+
+    /// Parses a move in UCI long algebraic notation and plays it if it is
+    /// legal.
+    pub fn make_move_uci(&mut self, uci: &str) -> Result<(), String> {
+        self.make_move(Move::from_uci(uci)?)
+    }
+
+    /// Checks whether a move is legal for the player to move. Only the
+    /// moves of the piece on `m.from()` are generated, so this is much
+    /// cheaper than searching [`Game::legal_moves`].
+    pub fn is_legal(&self, m: &Move) -> bool {
+        self.generated(*m).is_some()
+    }
+
+    /// Finds the legal move matching `m`, which has its flags filled in.
+    pub(super) fn generated(&self, m: Move) -> Option<Move> {
+        let piece = match self.board.position.get(m.from).copied().flatten() {
+            Some(piece) if piece.color == self.turn => piece,
+            _ => return None,
+        };
+        let mut moves = Vec::new();
+        self.piece_moves(m.from, piece, &mut moves);
+        moves
+            .into_iter()
+            .find(|&candidate| candidate == m)
+            .filter(|&candidate| self.is_legal_pseudo(candidate))
+    }
+
+    /// Plays a move from [`Game::legal_moves`] without checking it again.
+    pub(crate) fn make_legal_move(&mut self, m: Move) {
+        let mut undo = UndoInfo {
+            captured: None,
+            castling: self.castling,
+            en_passant: self.en_passant,
+            fifty_move_rule: self.fifty_move_rule,
+            hash: self.hash,
+            last_irreversible_ply: self.last_irreversible_ply,
+            position_history: None,
+        };
+        undo.captured = self.apply_move(m);
+        // No earlier position can come up again after a pawn move, capture,
+        // or loss of castling rights, so there is no need to remember them
+        if self.fifty_move_rule == 0 || self.castling != undo.castling {
+            undo.position_history = Some(std::mem::take(&mut self.position_history));
+        }
+        self.position_history.push(self.hash);
+        self.moves.push(m);
+        self.undo_info.push(undo);
+        if self.fifty_move_rule == 0 || m.is_castle() {
+            self.last_irreversible_ply = self.moves.len();
+        }
+    }
+
+    /// Checks whether a move captures a piece, including en passant.
+    pub fn is_capture(&self, m: &Move) -> bool {
+        let squares = (
+            self.board.position.get(m.from),
+            self.board.position.get(m.to),
+        );
+        match squares {
+            (Some(Some(piece)), Some(Some(target))) => piece.color != target.color,
+            (Some(Some(piece)), Some(None)) => {
+                piece.piece == PieceType::Pawn
+                    && self.en_passant == Some(m.to)
+                    && m.from % 8 != m.to % 8
+            }
+            _ => false,
+        }
+    }
+
+    /// Checks whether a move puts the opponent in check, directly or by
+    /// discovery.
+    pub fn is_check_move(&self, m: &Move) -> bool {
+        match self.generated(*m) {
+            Some(m) => {
+                let mut board = self.board.clone();
+                board.play(m);
+                match board.king(self.turn.opposite()) {
+                    Some((x, y)) => board.is_attacked(x, y, self.turn),
+                    None => false,
+                }
+            }
+            None => false,
+        }
+    }
+
+    /// The board as it would be after a move, without playing it in this
+    /// game. If the move is not legal the board is returned as it is.
+    pub fn peek_move(&self, m: &Move) -> Board {
+        let mut board = self.board.clone();
+        if let Some(m) = self.generated(*m) {
+            board.play(m);
+        }
+        board
+    }
+
+    /// Returns a copy of the game with a legal move played, leaving this
+    /// game untouched.
+    pub fn with_move(&self, m: Move) -> Result<Game, String> {
+        let mut game = self.clone();
+        game.make_move(m)?;
+        Ok(game)
+    }
+
+    /// Plays a generated move without checking that it is legal, updating
+    /// the castling rights, en passant square, and turn counters. The move's
+    /// flags say how pieces other than the one moving are affected.
+    /// Returns the piece captured, if any.
+    pub(super) fn apply_move(&mut self, m: Move) -> Option<Piece> {
+        let piece = self.board.position[m.from]?;
+        let (from_x, from_y) = Board::i_to_c(m.from);
+        let (to_x, to_y) = Board::i_to_c(m.to);
+        let mut captured = self.board.position[m.to];
+        // Taken out now and the new state put in at the end
+        self.hash ^= self.state_hash();
+
+        if m.flags.contains(MoveFlags::EN_PASSANT) {
+            // The captured pawn is beside the moving pawn, not on the target
+            captured = self.set_square(Board::c_to_i(to_x, from_y), None);
+        } else if m.is_castle() {
+            let (rook_from, rook_to) = if to_x > from_x { (7, 5) } else { (0, 3) };
+            let rook = self.set_square(Board::c_to_i(rook_from, from_y), None);
+            self.set_square(Board::c_to_i(rook_to, from_y), rook);
+        }
+
+        self.set_square(m.from, None);
+        self.set_square(
+            m.to,
+            Some(match m.promotion {
+                Some(promotion) => Piece {
+                    piece: promotion,
+                    color: piece.color,
+                },
+                None => piece,
+            }),
+        );
+
+        // Moving a king or rook, or capturing a rook, loses castling rights
+        if piece.piece == PieceType::King {
+            self.castling.remove(CastlingRights::both(piece.color));
+        }
+        for square in [m.from, m.to] {
+            let corner = match square {
+                63 => CastlingRights::WHITE_KINGSIDE,
+                56 => CastlingRights::WHITE_QUEENSIDE,
+                7 => CastlingRights::BLACK_KINGSIDE,
+                0 => CastlingRights::BLACK_QUEENSIDE,
+                _ => continue,
+            };
+            self.castling.remove(corner);
+        }
+
+        self.en_passant = if m.flags.contains(MoveFlags::DOUBLE_PUSH) {
+            Some(Board::c_to_i(from_x, (from_y + to_y) / 2))
+        } else {
+            None
+        };
+
+        if piece.piece == PieceType::Pawn || m.flags.contains(MoveFlags::CAPTURE) {
+            self.fifty_move_rule = 0;
+        } else {
+            self.fifty_move_rule += 1;
+        }
+
+        if self.turn == Color::Black {
+            self.full_turn_num += 1;
+        }
+        self.half_turn_num += 1;
+        self.turn = self.turn.opposite();
+        self.hash ^= self.state_hash();
+        captured
+    }
+
+    /// Puts a piece on a square, or clears it, keeping the hash and
+    /// material balance up to date. Returns what was there before.
+    pub(super) fn set_square(&mut self, index: usize, piece: Option<Piece>) -> Option<Piece> {
+        let old = std::mem::replace(&mut self.board.position[index], piece);
+        for (piece, sign) in [(old, -1), (piece, 1)] {
+            if let Some(piece) = piece {
+                self.hash ^= zobrist::piece_key(piece, index);
+                self.material += sign * piece.signed_value();
+            }
+        }
+        old
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game::{Board, Color, Game, Move, PieceType, STARTING_FEN};
+
+    #[test]
+    fn with_move_leaves_the_game_untouched() {
+        let fen = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+        let square = |name| Board::a_to_i(name).unwrap();
+        let game = Game::from_fen(fen).unwrap();
+        let next = game
+            .with_move(Move::new(square("e2"), square("e4"), None))
+            .unwrap();
+        assert!(game.board.position[square("e2")].is_some());
+        assert_eq!(game.turn, Color::White);
+        assert!(next.board.position[square("e2")].is_none());
+        assert!(next.board.position[square("e4")].is_some());
+        assert_eq!(next.turn, Color::Black);
+        assert_eq!(next.en_passant, Some(square("e3")));
+        assert!(game
+            .with_move(Move::new(square("e2"), square("e5"), None))
+            .is_err());
+    }
+
+    fn attacker_squares(board: &Board, square: &str, color: Color) -> Vec<String> {
+        let (x, y) = Board::i_to_c(Board::a_to_i(square).unwrap());
+        let mut squares: Vec<String> = board
+            .attackers_of(x, y, color)
+            .into_iter()
+            .map(|(x, y)| Board::i_to_a(Board::c_to_i(x, y)))
+            .collect();
+        squares.sort();
+        squares
+    }
+
+    #[test]
+    fn attackers_include_batteries() {
+        // The rook on e1 backs up the queen on e2, and the bishop on b2
+        // backs up the pawn on d4
+        let board = Board::from_fen("4k3/8/3n4/4p3/3P4/5N2/1B2Q3/4RK2").unwrap();
+        assert_eq!(
+            attacker_squares(&board, "e5", Color::White),
+            ["b2", "d4", "e1", "e2", "f3"]
+        );
+        assert_eq!(attacker_squares(&board, "d4", Color::White), ["b2", "f3"]);
+        assert_eq!(attacker_squares(&board, "d4", Color::Black), ["e5"]);
+    }
+
+    #[test]
+    fn promotions_expand_to_every_piece_on_every_target() {
+        // The pawn on e7 can push to e8 or take the rook on d8
+        let game = Game::from_fen("3r3k/4P3/8/8/8/8/8/K7 w - - 0 1").unwrap();
+        let from = Board::a_to_i("e7").unwrap();
+        let promotions: Vec<Move> = game
+            .legal_moves()
+            .into_iter()
+            .filter(|m| m.from == from)
+            .collect();
+        assert_eq!(promotions.len(), 8);
+        for target in ["d8", "e8"] {
+            for piece in [
+                PieceType::Queen,
+                PieceType::Rook,
+                PieceType::Bishop,
+                PieceType::Knight,
+            ] {
+                let m = Move::new(from, Board::a_to_i(target).unwrap(), Some(piece));
+                assert!(promotions.contains(&m), "{}", m.to_uci());
+            }
+        }
+        assert!(promotions.iter().all(|m| m.promotion.is_some()));
+        assert_eq!(
+            promotions
+                .iter()
+                .filter(|m| game.board.position[m.to].is_some())
+                .count(),
+            4
+        );
+    }
+
+    fn uci(game: &Game, text: &str) -> Move {
+        game.legal_moves()
+            .into_iter()
+            .find(|m| m.to_uci() == text)
+            .unwrap()
+    }
+
+    #[test]
+    fn captures_include_en_passant() {
+        let game = Game::from_fen("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 2").unwrap();
+        assert!(game.is_capture(&uci(&game, "e5d6")));
+        assert!(!game.is_capture(&uci(&game, "e5e6")));
+        assert!(!game.is_capture(&uci(&game, "e1d1")));
+
+        let game = Game::from_fen("4k3/8/8/3p4/4P3/8/8/4K3 w - - 0 1").unwrap();
+        assert!(game.is_capture(&uci(&game, "e4d5")));
+    }
+
+    #[test]
+    fn out_of_range_moves_are_not_captures() {
+        let game = Game::from_fen(STARTING_FEN).unwrap();
+        assert!(!game.is_capture(&Move::new(64, 0, None)));
+        assert!(!game.is_capture(&Move::new(52, 64, None)));
+        assert!(!game.is_capture(&Move::new(usize::MAX, usize::MAX, None)));
+        assert!(!game.is_legal(&Move::new(64, 0, None)));
+        assert!(!game.is_check_move(&Move::new(64, 0, None)));
+    }
+
+    fn uci_list(moves: Vec<Move>) -> Vec<String> {
+        moves.into_iter().map(Move::to_uci).collect()
+    }
+
+    #[test]
+    fn legal_moves_are_sorted() {
+        let game = Game::from_fen(STARTING_FEN).unwrap();
+        assert_eq!(
+            uci_list(game.legal_moves()).join(" "),
+            "b1a3 b1c3 g1f3 g1h3 a2a3 a2a4 b2b3 b2b4 c2c3 c2c4 d2d3 d2d4 \
+             e2e3 e2e4 f2f3 f2f4 g2g3 g2g4 h2h3 h2h4"
+        );
+        let game = Game::from_fen("4k3/1P6/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+        let moves = uci_list(game.legal_moves());
+        assert_eq!(
+            &moves[moves.len() - 4..],
+            ["b7b8q", "b7b8r", "b7b8b", "b7b8n"]
+        );
+    }
+
+    #[test]
+    fn en_passant_cannot_expose_the_king_along_the_rank() {
+        // Taking on c6 would take both pawns off the fifth rank, leaving
+        // the king on a5 open to the rook on h5
+        let game = Game::from_fen("8/8/8/KPp4r/8/8/8/7k w - c6 0 2").unwrap();
+        let capture = Move::from_uci("b5c6").unwrap();
+        assert!(!game.legal_moves().contains(&capture));
+        assert!(!game.is_legal(&capture));
+        assert!(game
+            .legal_moves()
+            .contains(&Move::from_uci("b5b6").unwrap()));
+
+        // Without the rook it is fine
+        let game = Game::from_fen("8/8/8/KPp5/8/8/8/7k w - c6 0 2").unwrap();
+        assert!(game.is_legal(&capture));
+    }
+
+    #[test]
+    fn single_moves_are_checked_for_legality() {
+        let game = Game::from_fen(STARTING_FEN).unwrap();
+        assert!(game.is_legal(&Move::from_uci("g1f3").unwrap()));
+        assert!(!game.is_legal(&Move::from_uci("g1g3").unwrap()));
+        // Not white's piece
+        assert!(!game.is_legal(&Move::from_uci("e7e5").unwrap()));
+
+        // The king cannot step into check or stay next to the rook
+        let game = Game::from_fen("4k3/8/8/8/8/8/3r4/4K3 w - - 0 1").unwrap();
+        assert!(!game.is_legal(&Move::from_uci("e1e2").unwrap()));
+        assert!(!game.is_legal(&Move::from_uci("e1f2").unwrap()));
+        assert!(game.is_legal(&Move::from_uci("e1d2").unwrap()));
+        assert_eq!(
+            game.clone().make_move(Move::from_uci("e1e2").unwrap()),
+            Err("Illegal move: e1e2".to_owned())
+        );
+    }
+
+    #[test]
+    fn is_legal_agrees_with_legal_moves() {
+        for fen in [
+            STARTING_FEN,
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        ] {
+            let game = Game::from_fen(fen).unwrap();
+            let legal = game.legal_moves();
+            for from in 0..64 {
+                for to in 0..64 {
+                    let m = Move::new(from, to, None);
+                    assert_eq!(game.is_legal(&m), legal.contains(&m), "{}", m.to_uci());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn castling_queries_check_the_path() {
+        let game = Game::from_fen(STARTING_FEN).unwrap();
+        assert!(!game.can_castle_kingside(Color::White));
+        assert!(!game.can_castle_queenside(Color::Black));
+
+        // The knight on b1 is in the way
+        let game = Game::from_fen("r3k2r/8/8/8/8/8/8/RN2K2R w KQkq - 0 1").unwrap();
+        assert!(game.can_castle_kingside(Color::White));
+        assert!(!game.can_castle_queenside(Color::White));
+        assert!(game.can_castle_kingside(Color::Black));
+        assert!(game.can_castle_queenside(Color::Black));
+
+        // f1 is attacked by the rook on f8, and black has no kingside right
+        let game = Game::from_fen("r3kr2/8/8/8/8/8/8/R3K2R w KQq - 0 1").unwrap();
+        assert!(!game.can_castle_kingside(Color::White));
+        assert!(game.can_castle_queenside(Color::White));
+        assert!(!game.can_castle_kingside(Color::Black));
+        assert!(game.can_castle_queenside(Color::Black));
+    }
+
+    #[test]
+    fn no_moves_once_the_game_is_over() {
+        let mut game = Game::from_moves(&["f3", "e5", "g4", "Qh4#"]).unwrap();
+        let fen = game.to_fen();
+        let err = game.make_move_uci("e2e3").unwrap_err();
+        assert!(err.starts_with("The game is over. Checkmate"), "{}", err);
+        assert!(game.make_move_san("e3").is_err());
+        assert_eq!(game.to_fen(), fen);
+
+        let mut game = Game::from_fen("7k/8/6K1/8/8/8/8/2B5 w - - 0 1").unwrap();
+        let err = game.make_move_uci("c1d2").unwrap_err();
+        assert!(err.contains("insufficient material"), "{}", err);
+    }
+
+    #[test]
+    fn attacked_squares_match_the_attack_map() {
+        for fen in [
+            STARTING_FEN,
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        ] {
+            let board = Board::from_fen(fen).unwrap();
+            for color in [Color::White, Color::Black] {
+                let squares: Vec<(usize, usize)> = board.attacked_squares(color).collect();
+                let map = board.attack_map(color);
+                let from_map: Vec<(usize, usize)> = (0..64)
+                    .filter(|&index| map[index])
+                    .map(Board::i_to_c)
+                    .collect();
+                // In index order, so each square comes up once
+                assert_eq!(squares, from_map);
+            }
+        }
+        let board = Board::from_fen(STARTING_FEN).unwrap();
+        assert_eq!(board.attacked_squares(Color::White).count(), 22);
+    }
+
+    #[test]
+    fn peek_move_matches_making_it() {
+        let cases = [
+            (STARTING_FEN, "e2e4"),
+            (STARTING_FEN, "g1f3"),
+            ("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1", "e1g1"),
+            ("r3k2r/8/8/8/8/8/8/R3K2R b KQkq - 0 1", "e8c8"),
+            (
+                "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+                "e5f6",
+            ),
+            ("3r4/4P3/8/8/8/8/8/k6K w - - 0 1", "e7d8n"),
+            (
+                "rnbqkbnr/ppp1pppp/8/3p4/4P3/8/PPPP1PPP/RNBQKBNR w KQkq - 0 2",
+                "e4d5",
+            ),
+        ];
+        for (fen, uci) in cases {
+            let game = Game::from_fen(fen).unwrap();
+            let m = Move::from_uci(uci).unwrap();
+            let peeked = game.peek_move(&m);
+            let played = game.with_move(m).unwrap();
+            assert_eq!(peeked.to_fen(), played.board().to_fen(), "{}", uci);
+            assert_eq!(game.to_fen(), fen);
+        }
+
+        // Illegal moves leave the board as it is
+        let game = Game::from_fen(STARTING_FEN).unwrap();
+        let peeked = game.peek_move(&Move::from_uci("e2e5").unwrap());
+        assert_eq!(peeked.to_fen(), game.board().to_fen());
+    }
+
+    #[test]
+    fn resolving_check() {
+        for fen in [
+            "4k3/8/8/8/8/8/4r3/R3K2R w KQ - 0 1",
+            "4k3/8/8/8/1b6/8/8/R2QK2R w KQ - 0 1",
+            "4k3/8/8/8/8/5n2/8/R3K1NR w KQ - 0 1",
+            "4k3/8/8/8/4r3/5n2/8/R3K2R w KQ - 0 1",
+            "8/8/8/2k5/3Pp3/8/8/4K3 b - d3 0 1",
+            "rnb1kbnr/pppp1ppp/8/4p3/6Pq/5P2/PPPPP2P/RNBQKBNR w KQkq - 1 3",
+        ] {
+            let game = Game::from_fen(fen).unwrap();
+            assert!(game.is_in_check(), "{}", fen);
+            assert_eq!(game.moves_resolving_check(), game.legal_moves(), "{}", fen);
+        }
+
+        let mut game = Game::play_random_game(11);
+        while game.undo().is_some() {
+            if game.is_in_check() {
+                assert_eq!(game.moves_resolving_check(), game.legal_moves());
+            } else {
+                assert!(game.moves_resolving_check().is_empty());
+            }
+        }
+    }
+
+    #[test]
+    fn legal_moves_where() {
+        let game =
+            Game::from_fen("r1bqkb1r/pppp1ppp/2n2n2/4p2Q/2B1P3/8/PPPP1PPP/RNB1K1NR w KQkq - 4 4")
+                .unwrap();
+        let captures = game.legal_moves_where(|m| game.is_capture(m));
+        let manual: Vec<Move> = game
+            .legal_moves()
+            .into_iter()
+            .filter(|m| game.is_capture(m))
+            .collect();
+        assert!(!captures.is_empty());
+        assert_eq!(captures, manual);
+        let knights = game.legal_moves_where(|m| {
+            game.board()
+                .get(m.from() % 8, m.from() / 8)
+                .is_some_and(|p| p.piece_type() == PieceType::Knight)
+        });
+        assert_eq!(knights.len(), 5);
+        assert_eq!(game.legal_moves_where(|_| true), game.legal_moves());
+    }
+
+    #[test]
+    fn defended_squares() {
+        let board = Game::from_fen("4k3/8/8/3p4/4p3/8/P7/4K3 w - - 0 1")
+            .unwrap()
+            .board()
+            .clone();
+        // The e4 pawn is protected by d5; neither d5 nor a2 is protected.
+        assert!(board.is_defended(4, 4, Color::Black));
+        assert!(!board.is_defended(3, 3, Color::Black));
+        assert!(!board.is_defended(0, 6, Color::White));
+        assert_eq!(
+            board.is_defended(3, 3, Color::Black),
+            !board.attackers_of(3, 3, Color::Black).is_empty()
+        );
+    }
+
+    #[test]
+    fn pawn_double_push() {
+        assert_eq!(Color::White.pawn_forward(), -1);
+        assert_eq!(Color::Black.pawn_forward(), 1);
+        let allows = |fen: &str, uci: &str| {
+            let game = Game::from_fen(fen).unwrap();
+            game.legal_moves().contains(&Move::from_uci(uci).unwrap())
+        };
+        assert!(allows("4k3/8/8/8/8/8/4P3/4K3 w - - 0 1", "e2e4"));
+        assert!(allows("4k3/4p3/8/8/8/8/8/4K3 b - - 0 1", "e7e5"));
+        assert!(!allows("4k3/8/8/8/8/4P3/8/4K3 w - - 0 1", "e3e5"));
+        assert!(!allows("4k3/8/4p3/8/8/8/8/4K3 b - - 0 1", "e6e4"));
+        assert!(!allows("4k3/4P3/8/8/8/8/8/4K3 w - - 0 1", "e7e5"));
+        assert!(!allows("4k3/8/8/8/8/8/4p3/4K3 b - - 0 1", "e2e4"));
+    }
+
+    #[test]
+    fn capture_moves_only() {
+        let game = Game::from_fen("4k3/8/3p4/6p1/4N3/8/8/4K3 w - - 0 1").unwrap();
+        let captures: Vec<String> = game
+            .capture_moves()
+            .into_iter()
+            .map(|m| m.to_uci())
+            .collect();
+        assert_eq!(captures, ["e4g5", "e4d6"]);
+        assert!(Game::from_fen(STARTING_FEN)
+            .unwrap()
+            .capture_moves()
+            .is_empty());
+        assert!(Game::from_fen("4k3/8/8/8/4N3/8/8/4K3 w - - 0 1")
+            .unwrap()
+            .capture_moves()
+            .is_empty());
+
+        let game = Game::from_fen("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1").unwrap();
+        assert_eq!(game.capture_moves(), [Move::from_uci("e5d6").unwrap()]);
+        // Quiet promotions count too, one per piece.
+        let game = Game::from_fen("4k3/P7/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+        assert_eq!(game.capture_moves().len(), 4);
+    }
+
+    #[test]
+    fn pinned_pieces() {
+        // The bishop on b4 pins the knight on c3 to the king.
+        let board = Board::from_fen("4k3/8/8/8/1b6/2N5/8/4K1N1").unwrap();
+        let c3 = Board::a_to_i("c3").unwrap();
+        assert_eq!(board.pinned_pieces(Color::White), [(c3, (1, 1))]);
+        assert!(board.pinned_pieces(Color::Black).is_empty());
+        // A second piece in the way means there is no pin.
+        let board = Board::from_fen("4k3/8/8/8/1b6/2N5/3P4/4K3").unwrap();
+        assert!(board.pinned_pieces(Color::White).is_empty());
+        // A rook cannot pin along a diagonal, but a queen can along a file.
+        let board = Board::from_fen("4q3/8/8/8/1r6/2N5/8/R3B1K1").unwrap();
+        assert!(board.pinned_pieces(Color::White).is_empty());
+        let board = Board::from_fen("4k3/4q3/8/8/8/4R3/8/4K3").unwrap();
+        let e3 = Board::a_to_i("e3").unwrap();
+        assert_eq!(board.pinned_pieces(Color::White), [(e3, (0, 1))]);
+    }
+
+    #[test]
+    fn board_checkers() {
+        let sq = |a: &str| Board::a_to_i(a).unwrap();
+        let board = Board::from_fen("4k3/8/8/8/8/8/8/4K2r").unwrap();
+        assert_eq!(board.checkers(Color::White), [sq("h1")]);
+        assert!(board.checkers(Color::Black).is_empty());
+        // A knight and a bishop together, as after a discovered check.
+        let fen = "4k3/8/8/8/1b6/3n4/8/4K3 w - - 0 1";
+        let mut checkers = Board::from_fen(fen).unwrap().checkers(Color::White);
+        checkers.sort();
+        assert_eq!(checkers, [sq("b4"), sq("d3")]);
+        let game = Game::from_fen(fen).unwrap();
+        assert!(game.legal_moves().iter().all(|m| m.from() == sq("e1")));
+        // A rook behind another rook does not give check.
+        let board = Board::from_fen("4k3/8/8/8/8/8/8/r2rK3").unwrap();
+        assert_eq!(board.checkers(Color::White), [sq("d1")]);
+        assert!(Board::from_fen(STARTING_FEN)
+            .unwrap()
+            .checkers(Color::White)
+            .is_empty());
+    }
+}
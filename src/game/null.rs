@@ -0,0 +1,125 @@
+use super::{Color, Game};
+
+impl Game {
+    /// Passes the turn to the other player without moving, which loses
+    /// the chance to capture en passant. The engine uses this to see
+    /// whether a position is so good that even giving the opponent a free
+    /// move leaves it winning. Passing is not allowed while in check, since
+    /// the king would be left attacked.
+    ///
+    /// The pass is not part of the game's history. It is taken back with
+    /// [`Game::undo_null_move`] before any move is made after it, or else by
+    /// [`Game::undo_to`] along with the moves around it.
+    pub fn make_null_move(&mut self) -> Result<(), String> {
+        if self.is_in_check() {
+            return Err("Cannot pass while in check".to_owned());
+        }
+        self.null_moves.push((self.ply(), self.en_passant));
+        self.hash ^= self.state_hash();
+        self.en_passant = None;
+        if self.turn == Color::Black {
+            self.full_turn_num += 1;
+        }
+        self.half_turn_num += 1;
+        self.turn = self.turn.opposite();
+        self.hash ^= self.state_hash();
+        Ok(())
+    }
+
+    /// Takes back the last [`Game::make_null_move`], giving the turn and
+    /// the en passant square back. Fails if there is no pass to take back,
+    /// or if a move has been played since.
+    pub fn undo_null_move(&mut self) -> Result<(), String> {
+        let en_passant = match self.null_moves.last() {
+            Some(&(ply, en_passant)) if ply == self.ply() => en_passant,
+            _ => return Err("There is no null move to undo".to_owned()),
+        };
+        self.null_moves.pop();
+        self.hash ^= self.state_hash();
+        self.turn = self.turn.opposite();
+        self.half_turn_num -= 1;
+        if self.turn == Color::Black {
+            self.full_turn_num -= 1;
+        }
+        self.en_passant = en_passant;
+        self.hash ^= self.state_hash();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{Move, STARTING_FEN};
+
+    #[test]
+    fn null_moves() {
+        let mut game =
+            Game::from_fen("rnbqkbnr/pppp1ppp/8/8/3pP3/8/PPP2PPP/RNBQKBNR b KQkq e3 0 3").unwrap();
+        let fen = game.to_fen();
+        let hash = game.hash();
+        game.make_null_move().unwrap();
+        assert_eq!(game.turn(), Color::White);
+        assert_eq!(
+            game.to_fen(),
+            "rnbqkbnr/pppp1ppp/8/8/3pP3/8/PPP2PPP/RNBQKBNR w KQkq - 0 4"
+        );
+        assert_eq!(game.hash(), game.position_hash());
+        game.undo_null_move().unwrap();
+        assert_eq!(game.to_fen(), fen);
+        assert_eq!(game.hash(), hash);
+        assert!(game.undo_null_move().is_err());
+
+        game.make_null_move().unwrap();
+        game.make_move_san("Nf3").unwrap();
+        assert!(game.undo_null_move().is_err());
+
+        let mut game = Game::from_fen("4k3/8/8/8/8/8/4r3/4K3 w - - 0 1").unwrap();
+        assert!(game.make_null_move().is_err());
+        assert_eq!(game.turn(), Color::White);
+    }
+
+    #[test]
+    fn undo_keeps_earlier_passes() {
+        let mut game = Game::from_fen(STARTING_FEN).unwrap();
+        game.make_move_uci("e2e4").unwrap();
+        game.make_null_move().unwrap();
+        game.make_move_uci("d2d4").unwrap();
+        game.make_move_uci("e7e5").unwrap();
+        assert_eq!(game.undo(), Some(Move::from_uci("e7e5").unwrap()));
+        assert_eq!(
+            game.to_fen(),
+            "rnbqkbnr/pppppppp/8/8/3PP3/8/PPP2PPP/RNBQKBNR b KQkq d3 0 2"
+        );
+        assert_eq!(game.hash(), game.position_hash());
+        // The pass is still there once d2d4 is taken back
+        game.undo();
+        assert_eq!(
+            game.to_fen(),
+            "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR w KQkq - 0 2"
+        );
+        game.undo_null_move().unwrap();
+        assert_eq!(
+            game.to_fen(),
+            "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1"
+        );
+    }
+
+    #[test]
+    fn undo_to_takes_back_later_passes() {
+        let mut game = Game::from_fen(STARTING_FEN).unwrap();
+        game.make_move_uci("e2e4").unwrap();
+        game.make_null_move().unwrap();
+        game.make_move_uci("d2d4").unwrap();
+        game.make_null_move().unwrap();
+        game.undo_to(0).unwrap();
+        assert_eq!(game.to_fen(), STARTING_FEN);
+        assert!(game.undo_null_move().is_err());
+        // A pass after the last move goes with it
+        game.make_move_uci("e2e4").unwrap();
+        game.make_null_move().unwrap();
+        assert_eq!(game.undo(), Some(Move::from_uci("e2e4").unwrap()));
+        assert_eq!(game.turn(), Color::White);
+        assert_eq!(game.hash(), game.position_hash());
+    }
+}
@@ -0,0 +1,93 @@
+use super::{Board, Color, Piece, PieceType};
+
+impl Board {
+    /// Packs the board into 32 bytes, 4 bits per square. Square `i` (see
+    /// [`Board::c_to_i`]) is the low nibble of byte `i / 2` when `i` is
+    /// even, and the high nibble otherwise.
+    ///
+    /// A nibble is `0` for an empty square. Otherwise the low 3 bits are
+    /// the piece type (`1` pawn, `2` rook, `3` knight, `4` bishop, `5`
+    /// queen, `6` king) and the high bit is set for black.
+    pub fn to_bytes(&self) -> [u8; 32] {
+        let mut bytes = [0; 32];
+        for (index, piece) in self.position.iter().enumerate() {
+            let nibble = match piece {
+                Some(piece) => {
+                    let kind = match piece.piece {
+                        PieceType::Pawn => 1,
+                        PieceType::Rook => 2,
+                        PieceType::Knight => 3,
+                        PieceType::Bishop => 4,
+                        PieceType::Queen => 5,
+                        PieceType::King => 6,
+                    };
+                    match piece.color {
+                        Color::White => kind,
+                        Color::Black => kind | 0b1000,
+                    }
+                }
+                None => 0,
+            };
+            bytes[index / 2] |= nibble << (4 * (index % 2));
+        }
+        bytes
+    }
+
+    /// Unpacks a board written by [`Board::to_bytes`].
+    pub fn from_bytes(bytes: &[u8; 32]) -> Result<Self, String> {
+        let mut board = Self::blank();
+        for index in 0..64 {
+            let nibble = (bytes[index / 2] >> (4 * (index % 2))) & 0b1111;
+            if nibble == 0 {
+                continue;
+            }
+            let piece = match nibble & 0b111 {
+                1 => PieceType::Pawn,
+                2 => PieceType::Rook,
+                3 => PieceType::Knight,
+                4 => PieceType::Bishop,
+                5 => PieceType::Queen,
+                6 => PieceType::King,
+                _ => return Err(format!("Invalid square nibble: {}", nibble)),
+            };
+            let color = if nibble & 0b1000 == 0 {
+                Color::White
+            } else {
+                Color::Black
+            };
+            board.position[index] = Some(Piece { piece, color });
+        }
+        Ok(board)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game::{Board, STARTING_FEN};
+
+    #[test]
+    fn bytes_round_trip() {
+        for fen in [
+            STARTING_FEN,
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "8/8/8/8/8/8/8/8",
+        ] {
+            let board = Board::from_fen(fen).unwrap();
+            let bytes = board.to_bytes();
+            assert_eq!(Board::from_bytes(&bytes).unwrap().to_fen(), board.to_fen());
+        }
+        // The white king sits in the low nibble of byte 30
+        let bytes = Board::from_fen("8/8/8/8/8/8/8/4K3").unwrap().to_bytes();
+        assert_eq!(bytes[30], 0x06);
+    }
+
+    #[test]
+    fn invalid_nibbles_are_rejected() {
+        let bytes = Board::from_fen(STARTING_FEN).unwrap().to_bytes();
+        for nibble in [0x07, 0x80, 0x0F] {
+            let mut bad = bytes;
+            bad[20] = nibble;
+            assert!(Board::from_bytes(&bad).is_err());
+        }
+    }
+}
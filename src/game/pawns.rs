@@ -0,0 +1,79 @@
+use super::{Board, Color, PieceType};
+
+impl Board {
+    /// Checks whether a pawn stands on `(x, y)` with no enemy pawn in front
+    /// of it on its own file or either neighbouring file, so nothing can
+    /// stop it or take it on its way to promoting.
+    pub fn is_passed_pawn(&self, x: usize, y: usize) -> bool {
+        let color = match self.get(x, y) {
+            Some(piece) if piece.piece == PieceType::Pawn => piece.color,
+            _ => return false,
+        };
+        let ahead = match color {
+            Color::White => 0..y,
+            Color::Black => y + 1..8,
+        };
+        !ahead.into_iter().any(|ay| {
+            (x.saturating_sub(1)..=x + 1)
+                .any(|ax| self.has(ax, ay, PieceType::Pawn, color.opposite()))
+        })
+    }
+
+    /// Counts the pawns of `color` standing on a file behind another of
+    /// their own, so two pawns on one file count as one doubled pawn.
+    pub fn doubled_pawns(&self, color: Color) -> usize {
+        self.pawns_per_file(color)
+            .iter()
+            .map(|&pawns| pawns.saturating_sub(1))
+            .sum()
+    }
+
+    /// Counts the pawns of `color` with no pawns of their own on either
+    /// neighbouring file to support them.
+    pub fn isolated_pawns(&self, color: Color) -> usize {
+        let files = self.pawns_per_file(color);
+        (0..8)
+            .filter(|&x| {
+                let left = x > 0 && files[x - 1] > 0;
+                let right = x < 7 && files[x + 1] > 0;
+                !left && !right
+            })
+            .map(|x| files[x])
+            .sum()
+    }
+
+    /// How many pawns of `color` are on each file, from the a-file to the
+    /// h-file.
+    fn pawns_per_file(&self, color: Color) -> [usize; 8] {
+        let mut files = [0; 8];
+        for (x, pawns) in files.iter_mut().enumerate() {
+            *pawns = (0..8)
+                .filter(|&y| self.has(x, y, PieceType::Pawn, color))
+                .count();
+        }
+        files
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game::{Board, Color};
+
+    #[test]
+    fn pawn_structure() {
+        // White has doubled and isolated pawns on the a-file, an isolated
+        // passed pawn on d4, and g2 and h2. Black's b7 pawn is isolated.
+        let board = Board::from_fen("4k3/1p3ppp/8/8/3P4/P7/P5PP/4K3").unwrap();
+        assert_eq!(board.doubled_pawns(Color::White), 1);
+        assert_eq!(board.doubled_pawns(Color::Black), 0);
+        assert_eq!(board.isolated_pawns(Color::White), 3);
+        assert_eq!(board.isolated_pawns(Color::Black), 1);
+        assert!(board.is_passed_pawn(3, 4));
+        // Held up by the b7 pawn
+        assert!(!board.is_passed_pawn(0, 5));
+        assert!(!board.is_passed_pawn(6, 6));
+        assert!(!board.is_passed_pawn(1, 1));
+        // Not a pawn
+        assert!(!board.is_passed_pawn(4, 4));
+    }
+}
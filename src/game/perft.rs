@@ -0,0 +1,131 @@
+use std::collections::HashMap;
+
+use super::{Game, Move, PositionKey};
+
+/// The most counts [`Game::perft_cached`] remembers. Once it is full, new
+/// counts are still worked out but no longer kept.
+const PERFT_CACHE_SIZE: usize = 1 << 20;
+
+impl Game {
+    /// Counts the positions reached after every sequence of `depth` legal
+    /// moves. Comparing these counts with known values is the standard way
+    /// to check move generation.
+    pub fn perft(&self, depth: usize) -> u64 {
+        if depth == 0 {
+            return 1;
+        }
+        let moves = self.legal_moves();
+        if depth == 1 {
+            return moves.len() as u64;
+        }
+        moves
+            .into_iter()
+            .map(|m| {
+                let mut game = self.clone();
+                game.apply_move(m);
+                game.perft(depth - 1)
+            })
+            .sum()
+    }
+
+    /// The same count as [`Game::perft`], but remembering the count below
+    /// each position it reaches, so positions reached by more than one
+    /// move order are only counted once for each depth.
+    pub fn perft_cached(&self, depth: usize) -> u64 {
+        self.perft_with(depth, &mut HashMap::new())
+    }
+
+    /// [`Game::perft`] using and filling in `cache`.
+    fn perft_with(&self, depth: usize, cache: &mut HashMap<(PositionKey, usize), u64>) -> u64 {
+        if depth <= 1 {
+            return self.perft(depth);
+        }
+        let key = (self.position_key(), depth);
+        if let Some(&count) = cache.get(&key) {
+            return count;
+        }
+        let count = self
+            .legal_moves()
+            .into_iter()
+            .map(|m| {
+                let mut game = self.clone();
+                game.apply_move(m);
+                game.perft_with(depth - 1, cache)
+            })
+            .sum();
+        if cache.len() < PERFT_CACHE_SIZE {
+            cache.insert(key, count);
+        }
+        count
+    }
+
+    /// Splits [`Game::perft`] up by the first move, in the order of
+    /// [`Game::legal_moves`], to narrow down where a count goes wrong.
+    pub fn perft_divide(&self, depth: usize) -> Vec<(Move, u64)> {
+        if depth == 0 {
+            return vec![];
+        }
+        self.legal_moves()
+            .into_iter()
+            .map(|m| {
+                let mut game = self.clone();
+                game.apply_move(m);
+                (m, game.perft(depth - 1))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game::{Game, STARTING_FEN};
+
+    const KIWIPETE: &str = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+
+    #[test]
+    fn perft_matches_known_counts() {
+        let cases: &[(&str, &[u64])] = &[
+            (STARTING_FEN, &[20, 400, 8902, 197281]),
+            (KIWIPETE, &[48, 2039, 97862]),
+            (
+                "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+                &[14, 191, 2812],
+            ),
+            (
+                "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+                &[6, 264, 9467],
+            ),
+            (
+                "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+                &[44, 1486],
+            ),
+        ];
+        for (fen, counts) in cases {
+            let game = Game::from_fen(fen).unwrap();
+            assert_eq!(game.perft(0), 1);
+            for (depth, &count) in counts.iter().enumerate() {
+                assert_eq!(game.perft(depth + 1), count, "{} depth {}", fen, depth + 1);
+            }
+        }
+    }
+
+    #[test]
+    fn divide_sums_to_perft() {
+        let game = Game::from_fen(KIWIPETE).unwrap();
+        let divide = game.perft_divide(2);
+        assert_eq!(divide.len(), 48);
+        assert_eq!(divide.iter().map(|(_, nodes)| nodes).sum::<u64>(), 2039);
+        let castle = divide.iter().find(|(m, _)| m.to_uci() == "e1g1").unwrap();
+        assert_eq!(castle.1, 43);
+    }
+
+    #[test]
+    fn perft_cache_matches() {
+        let game = Game::from_fen(STARTING_FEN).unwrap();
+        assert_eq!(game.perft_cached(0), 1);
+        assert_eq!(game.perft_cached(1), 20);
+        assert_eq!(game.perft_cached(4), game.perft(4));
+        let kiwipete = Game::from_fen(KIWIPETE).unwrap();
+        assert_eq!(kiwipete.perft_cached(3), 97_862);
+    }
+}
@@ -0,0 +1,555 @@
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use super::{Color, Game, GameResult, Move, STARTING_FEN};
+
+/// The longest a line of exported PGN movetext can be.
+const LINE_LENGTH: usize = 80;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+/// The tag pairs at the top of a PGN game. The Seven Tag Roster has its
+/// own fields, defaulting to the PGN placeholders for unknown values, and
+/// every other tag goes in `other`.
+pub struct PgnHeaders {
+    pub event: String,
+    pub site: String,
+    pub date: String,
+    pub round: String,
+    pub white: String,
+    pub black: String,
+    pub result: String,
+    pub other: BTreeMap<String, String>,
+}
+
+impl Default for PgnHeaders {
+    fn default() -> Self {
+        Self {
+            event: "?".to_owned(),
+            site: "?".to_owned(),
+            date: "????.??.??".to_owned(),
+            round: "?".to_owned(),
+            white: "?".to_owned(),
+            black: "?".to_owned(),
+            result: "*".to_owned(),
+            other: BTreeMap::new(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+/// The machine-readable commands found in the comments after a PGN move,
+/// as written by sites like lichess: `{[%clk 0:05:00] [%eval 0.17]}`.
+pub struct MoveAnnotations {
+    /// The time left on the mover's clock after the move.
+    pub clock: Option<Duration>,
+    /// The engine evaluation after the move, from white's point of view.
+    pub eval: Option<PgnEval>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+/// An engine evaluation from a `[%eval]` command
+pub enum PgnEval {
+    /// An advantage in centipawns, positive for white.
+    Centipawns(i32),
+    /// A forced mate in this many moves, negative if black mates.
+    Mate(i32),
+}
+
+impl MoveAnnotations {
+    /// Picks the `[%clk]` and `[%eval]` commands out of the comments after
+    /// a move. Other commands and ordinary text are skipped, and so are
+    /// commands that do not parse.
+    fn from_comments(comments: &[String]) -> Self {
+        let mut annotations = Self::default();
+        for comment in comments {
+            let mut rest = comment.as_str();
+            while let Some(start) = rest.find("[%") {
+                let end = match rest[start..].find(']') {
+                    Some(end) => start + end,
+                    None => break,
+                };
+                let command = &rest[start + 2..end];
+                rest = &rest[end + 1..];
+                match command.split_once(char::is_whitespace) {
+                    Some(("clk", value)) => {
+                        annotations.clock = Self::parse_clock(value.trim()).or(annotations.clock)
+                    }
+                    Some(("eval", value)) => {
+                        annotations.eval = Self::parse_eval(value.trim()).or(annotations.eval)
+                    }
+                    _ => {}
+                }
+            }
+        }
+        annotations
+    }
+
+    /// Parses a clock time like `1:05:00` or `0:00:09.5`.
+    fn parse_clock(time: &str) -> Option<Duration> {
+        let mut parts = time.rsplitn(3, ':');
+        let seconds: f64 = parts.next()?.parse().ok()?;
+        let minutes: u64 = parts.next().map_or(Some(0), |m| m.parse().ok())?;
+        let hours: u64 = parts.next().map_or(Some(0), |h| h.parse().ok())?;
+        let whole = hours
+            .checked_mul(3600)?
+            .checked_add(minutes.checked_mul(60)?)?;
+        Duration::from_secs(whole).checked_add(Duration::try_from_secs_f64(seconds).ok()?)
+    }
+
+    /// Parses an evaluation like `0.17`, `-1.5`, or `#-3`. Anything after a
+    /// comma, such as a search depth, is ignored.
+    fn parse_eval(eval: &str) -> Option<PgnEval> {
+        let eval = eval.split(',').next()?;
+        match eval.strip_prefix('#') {
+            Some(mate) => mate.parse().ok().map(PgnEval::Mate),
+            None => {
+                let pawns: f64 = eval.parse().ok()?;
+                pawns
+                    .is_finite()
+                    .then(|| PgnEval::Centipawns((pawns * 100.0).round() as i32))
+            }
+        }
+    }
+}
+
+/// A move from PGN movetext with the comments that follow it.
+pub(super) struct PgnMove {
+    pub(super) san: String,
+    comments: Vec<String>,
+}
+
+impl PgnMove {
+    /// The clock and evaluation commands in the move's comments.
+    pub(super) fn annotations(&self) -> MoveAnnotations {
+        MoveAnnotations::from_comments(&self.comments)
+    }
+}
+
+impl PgnHeaders {
+    /// Sets a tag, filling in the matching roster field if there is one.
+    pub fn set(&mut self, tag: &str, value: &str) {
+        let value = value.to_owned();
+        match tag {
+            "Event" => self.event = value,
+            "Site" => self.site = value,
+            "Date" => self.date = value,
+            "Round" => self.round = value,
+            "White" => self.white = value,
+            "Black" => self.black = value,
+            "Result" => self.result = value,
+            _ => {
+                self.other.insert(tag.to_owned(), value);
+            }
+        }
+    }
+
+    /// Gets a tag's value, whether or not it is part of the roster.
+    pub fn get(&self, tag: &str) -> Option<&str> {
+        match tag {
+            "Event" => Some(&self.event),
+            "Site" => Some(&self.site),
+            "Date" => Some(&self.date),
+            "Round" => Some(&self.round),
+            "White" => Some(&self.white),
+            "Black" => Some(&self.black),
+            "Result" => Some(&self.result),
+            _ => self.other.get(tag).map(String::as_str),
+        }
+    }
+
+    /// Every tag, the Seven Tag Roster first in its usual order, then the
+    /// others alphabetically.
+    fn tags(&self) -> impl Iterator<Item = (&str, &str)> {
+        [
+            ("Event", &self.event),
+            ("Site", &self.site),
+            ("Date", &self.date),
+            ("Round", &self.round),
+            ("White", &self.white),
+            ("Black", &self.black),
+            ("Result", &self.result),
+        ]
+        .into_iter()
+        .chain(self.other.iter().map(|(tag, value)| (tag.as_str(), value)))
+        .map(|(tag, value)| (tag, value.as_str()))
+    }
+
+    /// Parses a `[Tag "Value"]` line, with `\"` and `\\` escapes in the
+    /// value.
+    fn parse_tag(line: &str) -> Result<(String, String), String> {
+        let invalid = || format!("Invalid PGN tag: {}", line);
+        let inner = line
+            .strip_prefix('[')
+            .and_then(|line| line.strip_suffix(']'))
+            .ok_or_else(invalid)?;
+        let (tag, value) = inner
+            .trim()
+            .split_once(char::is_whitespace)
+            .ok_or_else(invalid)?;
+        let value = value
+            .trim()
+            .strip_prefix('"')
+            .and_then(|value| value.strip_suffix('"'))
+            .ok_or_else(invalid)?;
+
+        let mut unescaped = String::with_capacity(value.len());
+        let mut chars = value.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '\\' => unescaped.extend(chars.next()),
+                c => unescaped.push(c),
+            }
+        }
+        Ok((tag.to_owned(), unescaped))
+    }
+}
+
+impl Game {
+    /// Imports a single game in Portable Game Notation (PGN). The tag pairs
+    /// are kept in [`Game::headers`], and the game starts from the `FEN`
+    /// tag if there is one. Comments, variations, and numeric annotation
+    /// glyphs in the movetext are skipped.
+    pub fn from_pgn(pgn: &str) -> Result<Self, String> {
+        let (mut game, moves) = Self::parse_pgn(pgn)?;
+        for m in moves {
+            game.make_move_san(&m.san)?;
+        }
+        Ok(game)
+    }
+
+    /// Splits a PGN game into the starting position, with the tag pairs
+    /// filled in, and the moves of its main line.
+    pub(super) fn parse_pgn(pgn: &str) -> Result<(Self, Vec<PgnMove>), String> {
+        let mut headers = PgnHeaders::default();
+        let mut movetext = String::new();
+        for line in pgn.lines() {
+            let line = line.trim();
+            if line.starts_with('[') && movetext.trim().is_empty() {
+                let (tag, value) = PgnHeaders::parse_tag(line)?;
+                headers.set(&tag, &value);
+            } else if !line.starts_with('%') {
+                movetext.push_str(line);
+                movetext.push('\n');
+            }
+        }
+
+        let fen = headers
+            .other
+            .get("FEN")
+            .map_or(STARTING_FEN, String::as_str);
+        let mut game = Game::from_fen(fen).map_err(|e| e.to_string())?;
+        let moves = Self::movetext_moves(&movetext)?;
+        game.headers = headers;
+        Ok((game, moves))
+    }
+
+    /// Splits PGN movetext into the SAN moves of the main line, each with
+    /// the comments that follow it.
+    fn movetext_moves(movetext: &str) -> Result<Vec<PgnMove>, String> {
+        let mut moves: Vec<PgnMove> = vec![];
+        let mut depth = 0;
+        let mut rest = movetext;
+        while let Some(c) = rest.chars().next() {
+            match c {
+                '{' => match rest.find('}') {
+                    Some(end) => {
+                        if let (0, Some(last)) = (depth, moves.last_mut()) {
+                            last.comments.push(rest[1..end].to_owned());
+                        }
+                        rest = &rest[end + 1..];
+                        continue;
+                    }
+                    None => return Err("Unterminated PGN comment".to_owned()),
+                },
+                ';' => {
+                    rest = rest.find('\n').map_or("", |end| &rest[end..]);
+                    continue;
+                }
+                '(' => depth += 1,
+                ')' if depth > 0 => depth -= 1,
+                ')' => return Err("Unmatched ')' in PGN".to_owned()),
+                c if c.is_whitespace() => {}
+                _ => {
+                    let end = rest
+                        .find(|c: char| c.is_whitespace() || "{};()".contains(c))
+                        .unwrap_or(rest.len());
+                    let token = &rest[..end];
+                    rest = &rest[end..];
+                    if depth == 0 {
+                        // Move numbers may be stuck to the move, as in `1.e4`
+                        let san = token.rsplit('.').next().unwrap_or(token);
+                        let result = matches!(token, "1-0" | "0-1" | "1/2-1/2" | "*");
+                        if !san.is_empty() && !san.starts_with('$') && !result {
+                            moves.push(PgnMove {
+                                san: san.to_owned(),
+                                comments: vec![],
+                            });
+                        }
+                    }
+                    continue;
+                }
+            }
+            rest = &rest[c.len_utf8()..];
+        }
+        if depth > 0 {
+            return Err("Unterminated PGN variation".to_owned());
+        }
+        Ok(moves)
+    }
+
+    /// Exports the game in Portable Game Notation (PGN). The `Result` tag
+    /// is filled in if the game has ended, and games that did not start
+    /// from the standard position get `SetUp` and `FEN` tags.
+    pub fn to_pgn(&self) -> String {
+        let mut headers = self.headers.clone();
+        if self.result() != GameResult::Ongoing {
+            headers.result = self.result().to_pgn_token().to_owned();
+        }
+        if self.start_fen != STARTING_FEN {
+            headers.set("SetUp", "1");
+            headers.set("FEN", &self.start_fen);
+        }
+
+        let mut pgn = String::new();
+        for (tag, value) in headers.tags() {
+            let value = value.replace('\\', "\\\\").replace('"', "\\\"");
+            pgn.push_str(&format!("[{} \"{}\"]\n", tag, value));
+        }
+        pgn.push('\n');
+
+        let tokens = self.movetext_tokens().into_iter().chain([headers.result]);
+        pgn.push_str(&Self::wrap_tokens(tokens));
+        pgn.push('\n');
+        pgn
+    }
+
+    /// The moves of the game in numbered SAN, like `1. e4 e5 2. Nf3 Nc6`,
+    /// without any tags or result. Long games are wrapped onto several
+    /// lines, as in [`Game::to_pgn`].
+    pub fn movetext(&self) -> String {
+        Self::wrap_tokens(self.movetext_tokens())
+    }
+
+    /// Joins tokens with spaces, starting a new line before any token that
+    /// would make the line longer than [`LINE_LENGTH`].
+    fn wrap_tokens(tokens: impl IntoIterator<Item = String>) -> String {
+        let mut text = String::new();
+        let mut line_length = 0;
+        for token in tokens {
+            if line_length > 0 && line_length + 1 + token.len() > LINE_LENGTH {
+                text.push('\n');
+                line_length = 0;
+            } else if line_length > 0 {
+                text.push(' ');
+                line_length += 1;
+            }
+            line_length += token.len();
+            text.push_str(&token);
+        }
+        text
+    }
+
+    /// Writes a line of moves played from the current position as numbered
+    /// PGN movetext, for sharing a variation rather than the game itself.
+    /// Numbering carries on from the current move, so a line starting with
+    /// black to move starts with a number like `12...`. The moves of a
+    /// [`GameTree`](super::GameTree) variation can be taken from
+    /// [`GameTree::moves_to`](super::GameTree::moves_to).
+    pub fn pgn_from_variation(&self, moves: &[Move]) -> Result<String, String> {
+        Ok(Self::wrap_tokens(Self::line_tokens(self.clone(), moves)?))
+    }
+
+    /// The moves of the game in SAN, each white move preceded by its move
+    /// number. A game starting with black to move starts with a number
+    /// like `1...`. A game cannot be replayed past a pass made with
+    /// [`Game::make_null_move`], so the moves stop at the first one.
+    fn movetext_tokens(&self) -> Vec<String> {
+        let end = self.null_moves.first().map_or(self.ply(), |&(ply, _)| ply);
+        Self::line_tokens(self.start(), &self.moves[..end])
+            .expect("the moves played replay from the start")
+    }
+
+    /// The tokens of [`Game::movetext_tokens`] for `moves` played from
+    /// `game`, or an error naming the first move that is not legal.
+    fn line_tokens(mut game: Game, moves: &[Move]) -> Result<Vec<String>, String> {
+        let mut tokens = vec![];
+        for &m in moves {
+            let m = game
+                .generated(m)
+                .ok_or_else(|| format!("Illegal move: {}", m.to_uci()))?;
+            if game.turn == Color::White {
+                tokens.push(format!("{}.", game.full_turn_num));
+            } else if tokens.is_empty() {
+                tokens.push(format!("{}...", game.full_turn_num));
+            }
+            tokens.push(game.move_to_san(m)?);
+            game.make_legal_move(m);
+        }
+        Ok(tokens)
+    }
+
+    /// The PGN tag pairs of the game, if it was imported from PGN.
+    pub fn headers(&self) -> &PgnHeaders {
+        &self.headers
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{Game, GameTree};
+
+    #[test]
+    fn headers_are_parsed() {
+        let pgn = r#"[Event "Casual"]
+[Site "?"]
+[Date "2024.01.01"]
+[Round "1"]
+[White "A \"Quoted\" Player"]
+[Black "B"]
+[Result "1-0"]
+[ECO "C50"]
+
+1. e4 e5 2. Qh5 {threat} Nc6 (2... g6 3. Qxe5+) 3.Bc4 Nf6?? $4 4. Qxf7# 1-0
+"#;
+        let game = Game::from_pgn(pgn).unwrap();
+        let headers = game.headers();
+        assert_eq!(headers.event, "Casual");
+        assert_eq!(headers.date, "2024.01.01");
+        assert_eq!(headers.white, "A \"Quoted\" Player");
+        assert_eq!(headers.black, "B");
+        assert_eq!(headers.get("ECO"), Some("C50"));
+        assert_eq!(headers.get("Annotator"), None);
+        assert_eq!(headers.result, game.result().to_pgn_token());
+        assert!(game.is_checkmate());
+    }
+
+    #[test]
+    fn movetext_without_headers() {
+        let game = Game::from_pgn(
+            "1. e4 e5 2. Nf3 Nc6 3. Bb5 a6 4. O-O Nf6 5. d4 exd4 6. e5 Ne4 7. Re1 d5 8. exd6 Bxd6",
+        )
+        .unwrap();
+        assert_eq!(
+            game.board.to_fen(),
+            "r1bqk2r/1pp2ppp/p1nb4/1B6/3pn3/5N2/PPP2PPP/RNBQR1K1"
+        );
+        assert_eq!(game.headers().event, "?");
+        assert!(Game::from_pgn("1. e4 e5 2. Ke3").is_err());
+    }
+
+    #[test]
+    fn parses_clock_and_eval_comments() {
+        let pgn = "[Event \"x\"]\n\n1. e4 { [%eval 0.17] [%clk 0:05:00] } 1... e5 { [%clk 0:04:58.5] [%eval #-3] } (1... c5 { [%clk 1:00:00] }) 2. Nf3 { just a comment } *\n";
+        let tree = GameTree::from_pgn(pgn).unwrap();
+        let line = tree.mainline();
+        assert_eq!(line.len(), 4);
+        let first = tree.annotations_at(line[1]).unwrap();
+        assert_eq!(first.clock, Some(Duration::from_secs(300)));
+        assert_eq!(first.eval, Some(PgnEval::Centipawns(17)));
+        let second = tree.annotations_at(line[2]).unwrap();
+        assert_eq!(second.clock, Some(Duration::from_millis(298_500)));
+        assert_eq!(second.eval, Some(PgnEval::Mate(-3)));
+        assert_eq!(
+            tree.annotations_at(line[3]).unwrap(),
+            &MoveAnnotations::default()
+        );
+        assert_eq!(Game::from_pgn(pgn).unwrap().history().len(), 3);
+    }
+
+    #[test]
+    fn clock_parsing() {
+        let clock = MoveAnnotations::parse_clock;
+        assert_eq!(clock("1:05:00"), Some(Duration::from_secs(3900)));
+        assert_eq!(clock("0:00:09.5"), Some(Duration::from_millis(9500)));
+        assert_eq!(clock("42"), Some(Duration::from_secs(42)));
+        assert_eq!(clock("3:07"), Some(Duration::from_secs(187)));
+
+        assert_eq!(clock(""), None);
+        assert_eq!(clock("a:00:00"), None);
+        assert_eq!(clock("0:00:-1"), None);
+        assert_eq!(clock("0:00:NaN"), None);
+        assert_eq!(clock("0:00:inf"), None);
+        assert_eq!(clock("1e30"), None);
+        assert_eq!(clock("99999999999999999:00:00"), None);
+        assert_eq!(clock("0:999999999999999999999:00"), None);
+        assert_eq!(clock(&format!("{}:00:00", u64::MAX / 3600 + 1)), None);
+    }
+
+    #[test]
+    fn malformed_clock_comments_are_skipped() {
+        let comments = vec!["[%clk 1e30] [%clk 99999999999999999:00:00]".to_owned()];
+        assert_eq!(MoveAnnotations::from_comments(&comments).clock, None);
+        let comments = vec!["[%clk 0:01:00] [%clk 1e30]".to_owned()];
+        assert_eq!(
+            MoveAnnotations::from_comments(&comments).clock,
+            Some(Duration::from_secs(60))
+        );
+    }
+
+    #[test]
+    fn movetext_export() {
+        let game = Game::from_moves(&["e4", "e5", "Nf3", "Nc6"]).unwrap();
+        assert_eq!(game.movetext(), "1. e4 e5 2. Nf3 Nc6");
+        assert!(game.to_pgn().ends_with("\n\n1. e4 e5 2. Nf3 Nc6 *\n"));
+
+        let mut game =
+            Game::from_fen("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1").unwrap();
+        game.make_move_san("e5").unwrap();
+        game.make_move_san("Nf3").unwrap();
+        assert_eq!(game.movetext(), "1... e5 2. Nf3");
+        assert_eq!(Game::from_fen(STARTING_FEN).unwrap().movetext(), "");
+
+        let game = Game::play_random_game(3);
+        assert!(game.movetext().lines().count() > 1);
+        assert!(game.movetext().lines().all(|line| line.len() <= 80));
+    }
+
+    #[test]
+    fn pgn_from_variation() {
+        let mut tree = GameTree::from_pgn("1. e4 e5 2. Nf3 Nc6 3. Bb5").unwrap();
+        // Branch off after 2. Nf3 with 2... d6 3. d4.
+        let after_nf3 = tree.mainline()[3];
+        tree.go_to(after_nf3).unwrap();
+        tree.add_move(Move::from_uci("d7d6").unwrap()).unwrap();
+        let end = tree.add_move(Move::from_uci("d2d4").unwrap()).unwrap();
+
+        let start = tree.game_at(after_nf3).unwrap();
+        let text = start.pgn_from_variation(&tree.moves_to(end)[3..]).unwrap();
+        assert_eq!(text, "2... d6 3. d4");
+        let pgn = format!("[SetUp \"1\"]\n[FEN \"{}\"]\n\n{} *", start.to_fen(), text);
+        assert_eq!(
+            Game::from_pgn(&pgn).unwrap().to_fen(),
+            tree.game_at(end).unwrap().to_fen()
+        );
+
+        let whole = Game::from_fen(STARTING_FEN)
+            .unwrap()
+            .pgn_from_variation(&tree.moves_to(end))
+            .unwrap();
+        assert_eq!(whole, "1. e4 e5 2. Nf3 d6 3. d4");
+        assert!(start
+            .pgn_from_variation(&[Move::from_uci("e2e4").unwrap()])
+            .is_err());
+        assert_eq!(start.pgn_from_variation(&[]).unwrap(), "");
+    }
+
+    #[test]
+    fn pgn_from_variation_fills_in_move_flags() {
+        let game = Game::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1").unwrap();
+        let line: Vec<Move> = ["e1g1", "a8b8", "f1f2"]
+            .iter()
+            .map(|uci| Move::from_uci(uci).unwrap())
+            .collect();
+        let text = game.pgn_from_variation(&line).unwrap();
+        assert_eq!(text, "1. O-O Rb8 2. Rf2");
+        let pgn = format!("[SetUp \"1\"]\n[FEN \"{}\"]\n\n{} *", game.to_fen(), text);
+        assert_eq!(
+            Game::from_pgn(&pgn).unwrap().to_fen(),
+            "1r2k2r/8/8/8/8/8/5R2/R5K1 b k - 3 2"
+        );
+        // Without the castle the rook is still on h1, so f1f2 is illegal.
+        assert!(game.pgn_from_variation(&line[1..]).is_err());
+    }
+}
@@ -0,0 +1,80 @@
+use super::{Board, PieceType};
+
+/// The phase weight of a board with all of its starting pieces.
+pub const FULL_PHASE: usize = 24;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+/// How far a game has progressed, judged by the pieces left on the board
+pub enum Phase {
+    Opening,
+    Middlegame,
+    Endgame,
+}
+
+impl Board {
+    /// Weighs the knights, bishops, rooks, and queens left on the board,
+    /// from [`FULL_PHASE`] for all of the starting pieces down to 0 for
+    /// kings and pawns only. Pawns do not count. Extra pieces from
+    /// promotion do not push it past [`FULL_PHASE`].
+    pub fn phase_weight(&self) -> usize {
+        let weight: usize = self
+            .position
+            .iter()
+            .flatten()
+            .map(|piece| match piece.piece {
+                PieceType::Knight | PieceType::Bishop => 1,
+                PieceType::Rook => 2,
+                PieceType::Queen => 4,
+                PieceType::Pawn | PieceType::King => 0,
+            })
+            .sum();
+        weight.min(FULL_PHASE)
+    }
+
+    /// The phase of the game. It stays the opening until more than a
+    /// couple of minor pieces are traded, and becomes the endgame once
+    /// each side has no more than about a rook and a minor piece.
+    pub fn game_phase(&self) -> Phase {
+        match self.phase_weight() {
+            20.. => Phase::Opening,
+            7..=19 => Phase::Middlegame,
+            _ => Phase::Endgame,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::STARTING_FEN;
+
+    fn board(fen: &str) -> Board {
+        Board::from_fen(fen).unwrap()
+    }
+
+    #[test]
+    fn phase_weights() {
+        assert_eq!(board(STARTING_FEN).phase_weight(), FULL_PHASE);
+        assert_eq!(board("4k3/pppppppp/8/8/8/8/PPPPPPPP/4K3").phase_weight(), 0);
+        assert_eq!(board("r3k3/8/8/8/8/8/8/R2QK2R").phase_weight(), 10);
+        // Promoted queens do not go past the full weight
+        assert_eq!(
+            board("qqqqk3/8/8/8/8/8/8/QQQQK3").phase_weight(),
+            FULL_PHASE
+        );
+    }
+
+    #[test]
+    fn phases() {
+        assert_eq!(board(STARTING_FEN).game_phase(), Phase::Opening);
+        assert_eq!(
+            board("r3k3/8/8/8/8/8/8/R2QK2R").game_phase(),
+            Phase::Middlegame
+        );
+        assert_eq!(board("4k3/8/8/8/8/8/4P3/4K3").game_phase(), Phase::Endgame);
+        assert_eq!(
+            board("2r1k3/8/8/8/8/8/8/2R1KB2").game_phase(),
+            Phase::Endgame
+        );
+    }
+}
@@ -0,0 +1,129 @@
+use super::{Board, CastlingRights, Color, Game, Piece, PieceType};
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+/// Everything that makes two positions the same for the repetition rules:
+/// the pieces, the player to move, the castling rights, and the en passant
+/// square, but only when a pawn could actually capture onto it. The move
+/// counters are left out.
+pub struct PositionKey {
+    position: [Option<Piece>; 64],
+    turn: Color,
+    castling: CastlingRights,
+    en_passant: Option<usize>,
+}
+
+impl Game {
+    /// The [`PositionKey`] of the current position.
+    pub fn position_key(&self) -> PositionKey {
+        PositionKey {
+            position: self.board.position,
+            turn: self.turn,
+            castling: self.castling,
+            en_passant: self.capturable_en_passant(),
+        }
+    }
+
+    /// The en passant square, if a pawn of the player to move stands next
+    /// to the pawn that skipped over it.
+    pub(super) fn capturable_en_passant(&self) -> Option<usize> {
+        let (x, y) = Board::i_to_c(self.en_passant?);
+        let (_, pawn_y) = Board::offset(x, y, 0, -self.turn.pawn_direction())?;
+        [-1, 1]
+            .into_iter()
+            .filter_map(|dx| Board::offset(x, pawn_y, dx, 0))
+            .any(|(px, py)| self.board.has(px, py, PieceType::Pawn, self.turn))
+            .then_some(Board::c_to_i(x, y))
+    }
+
+    /// How many times the current position has occurred in the game,
+    /// counting this time.
+    pub fn repetition_count(&self) -> usize {
+        let current = self.hash;
+        self.position_history
+            .iter()
+            .filter(|&&hash| hash == current)
+            .count()
+    }
+
+    /// Checks whether the current position has occurred at least three
+    /// times, which lets either player claim a draw.
+    pub fn is_threefold_repetition(&self) -> bool {
+        self.repetition_count() >= 3
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game::{Game, Move, STARTING_FEN};
+
+    fn play(game: &mut Game, line: &str) {
+        for uci in line.split_whitespace() {
+            game.make_move(Move::from_uci(uci).unwrap()).unwrap();
+        }
+    }
+
+    #[test]
+    fn repetitions_are_counted() {
+        let mut game = Game::from_fen(STARTING_FEN).unwrap();
+        assert_eq!(game.repetition_count(), 1);
+        play(&mut game, "g1f3 g8f6 f3g1 f6g8");
+        assert_eq!(game.repetition_count(), 2);
+        assert!(!game.is_threefold_repetition());
+        play(&mut game, "g1f3 g8f6 f3g1 f6g8");
+        assert_eq!(game.repetition_count(), 3);
+        assert!(game.is_threefold_repetition());
+        play(&mut game, "g1f3");
+        assert_eq!(game.repetition_count(), 3);
+    }
+
+    fn after(line: &str) -> Game {
+        let mut game = Game::from_fen(STARTING_FEN).unwrap();
+        play(&mut game, line);
+        game
+    }
+
+    #[test]
+    fn position_keys_ignore_move_order_and_counters() {
+        let a = after("g1f3 g8f6 b1c3 b8c6");
+        let b = after("b1c3 b8c6 g1f3 g8f6");
+        assert_eq!(a.position_key(), b.position_key());
+        assert_eq!(a.position_hash(), b.position_hash());
+        let start = Game::from_fen(STARTING_FEN).unwrap();
+        let back = after("g1f3 g8f6 f3g1 f6g8");
+        assert_eq!(start.position_key(), back.position_key());
+        assert_ne!(start.to_fen(), back.to_fen());
+
+        // No pawn can take on e6, so the en passant square does not count
+        let direct = after("e2e4 e7e5");
+        let slow = after("e2e3 e7e6 e3e4 e6e5");
+        assert_eq!(direct.position_key(), slow.position_key());
+        assert_ne!(direct.to_fen(), slow.to_fen());
+        assert_ne!(after("e2e4").position_key(), direct.position_key());
+    }
+
+    #[test]
+    fn capturable_en_passant_counts() {
+        // The pawn on d4 can take on e3
+        let ep = Game::from_fen("4k3/8/8/8/3pP3/8/8/4K3 b - e3 0 1").unwrap();
+        let no_ep = Game::from_fen("4k3/8/8/8/3pP3/8/8/4K3 b - - 0 1").unwrap();
+        assert_ne!(ep.position_key(), no_ep.position_key());
+        assert_eq!(ep.capturable_en_passant(), Some(44));
+
+        let far = Game::from_fen("4k3/8/8/8/p3P3/8/8/4K3 b - e3 0 1").unwrap();
+        assert_eq!(far.capturable_en_passant(), None);
+    }
+
+    #[test]
+    fn history_before_irreversible_moves_is_dropped() {
+        let mut game = Game::from_fen("4k3/8/8/3p4/8/8/8/R3K2N w - - 0 1").unwrap();
+        play(&mut game, "h1g3 e8e7 g3h1 e7e8 h1g3 e8e7 g3h1 e7e8");
+        assert_eq!(game.position_history.len(), 9);
+        assert_eq!(game.repetition_count(), 3);
+        // Rxd5 is a capture, so nothing before it can repeat
+        play(&mut game, "a1a5 e8e7 a5d5");
+        assert_eq!(game.position_history.len(), 1);
+        assert_eq!(game.repetition_count(), 1);
+        play(&mut game, "e7e8 d5d6 e8e7 d6d5 e7e8 d5d6 e8e7 d6d5");
+        assert_eq!(game.repetition_count(), 3);
+    }
+}
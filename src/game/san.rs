@@ -0,0 +1,268 @@
+use super::{Board, Game, GameStatus, Move, MoveFlags, PieceType};
+
+impl Game {
+    /// Finds the legal move described by a move in Standard Algebraic
+    /// Notation (SAN), such as `"Nf3"`, `"exd5"`, `"O-O"`, or `"e8=Q+"`.
+    /// Promotions may also be written without the `=` or with a lowercase
+    /// letter, as in `"e8Q"` or `"e8=q"`.
+    pub fn san_to_move(&self, san: &str) -> Result<Move, String> {
+        let invalid = || format!("Invalid SAN move: {}", san);
+        let stripped = san.trim_end_matches(['+', '#', '!', '?']);
+
+        let castle_to = match stripped {
+            "O-O" | "0-0" => Some(6),
+            "O-O-O" | "0-0-0" => Some(2),
+            _ => None,
+        };
+        if let Some(to_x) = castle_to {
+            let (x, y) = self.board.king(self.turn).ok_or_else(invalid)?;
+            let castle = Move::new(Board::c_to_i(x, y), Board::c_to_i(to_x, y), None);
+            return match self.legal_moves().into_iter().find(|&m| m == castle) {
+                Some(m) if x == 4 => Ok(m),
+                _ => Err(format!("Illegal move: {}", san)),
+            };
+        }
+
+        // Some writers leave out the `=`, as in `e8Q`, but a letter straight
+        // after the rank can only be a promotion
+        let split = stripped.split_once('=').or_else(|| {
+            let mut chars = stripped.char_indices().rev();
+            match (chars.next(), chars.next()) {
+                (Some((i, c)), Some((_, rank)))
+                    if c.is_ascii_alphabetic() && rank.is_ascii_digit() =>
+                {
+                    Some(stripped.split_at(i))
+                }
+                _ => None,
+            }
+        });
+        let (body, promotion) = match split {
+            Some((body, promotion)) => {
+                let promotion = match promotion.parse::<PieceType>() {
+                    Ok(PieceType::Pawn | PieceType::King) | Err(_) => return Err(invalid()),
+                    Ok(promotion) => promotion,
+                };
+                (body, Some(promotion))
+            }
+            None => (stripped, None),
+        };
+        if !body.is_ascii() || body.len() < 2 {
+            return Err(invalid());
+        }
+
+        let (prefix, target) = body.split_at(body.len() - 2);
+        let to = Board::a_to_i(target).ok_or_else(invalid)?;
+        let (piece, prefix) = match prefix.chars().next() {
+            Some(c @ ('K' | 'Q' | 'R' | 'B' | 'N')) => (PieceType::from_char(c), &prefix[1..]),
+            _ => (Some(PieceType::Pawn), prefix),
+        };
+        let piece = piece.ok_or_else(invalid)?;
+
+        // Anything left over narrows down which piece moved
+        let (mut from_x, mut from_y) = (None, None);
+        for c in prefix.trim_end_matches('x').chars() {
+            match c {
+                'a'..='h' if from_x.is_none() => from_x = Some(c as usize - 'a' as usize),
+                '1'..='8' if from_y.is_none() => from_y = Some('8' as usize - c as usize),
+                _ => return Err(invalid()),
+            }
+        }
+
+        let mut candidates = self.legal_moves().into_iter().filter(|m| {
+            let (x, y) = Board::i_to_c(m.from);
+            m.to == to
+                && m.promotion == promotion
+                && matches!(self.board.position[m.from], Some(p) if p.piece == piece)
+                && from_x.is_none_or(|from_x| from_x == x)
+                && from_y.is_none_or(|from_y| from_y == y)
+        });
+        match (candidates.next(), candidates.next()) {
+            (Some(m), None) => Ok(m),
+            (Some(_), Some(_)) => Err(format!("Ambiguous move: {}", san)),
+            (None, _) => Err(format!("Illegal move: {}", san)),
+        }
+    }
+
+    /// Parses a move in Standard Algebraic Notation (SAN) and plays it, as
+    /// long as the game is not over.
+    pub fn make_move_san(&mut self, san: &str) -> Result<(), String> {
+        self.make_move(self.san_to_move(san)?)
+    }
+
+    /// Writes a legal move in Standard Algebraic Notation (SAN), adding
+    /// the file, rank, or both of the square moved from when another piece
+    /// of the same type could also legally move to the same square. Moves that
+    /// give check end in `+` and checkmates end in `#`.
+    pub fn move_to_san(&self, m: Move) -> Result<String, String> {
+        let m = self
+            .generated(m)
+            .ok_or_else(|| format!("Illegal move: {}", m.to_uci()))?;
+        let piece = match self.board.position[m.from] {
+            Some(piece) => piece.piece,
+            None => return Err(format!("Illegal move: {}", m.to_uci())),
+        };
+        let (from_x, from_y) = Board::i_to_c(m.from);
+        let (to_x, _) = Board::i_to_c(m.to);
+
+        let mut san = if m.is_castle() {
+            if to_x > from_x { "O-O" } else { "O-O-O" }.to_owned()
+        } else {
+            let mut san = String::new();
+            let capture = m.flags.contains(MoveFlags::CAPTURE);
+            if piece == PieceType::Pawn {
+                if capture {
+                    san.push(Board::i_to_a(m.from).remove(0));
+                }
+            } else {
+                san.push(piece.as_char());
+                // Only legal moves count, so a piece that could reach the
+                // square but is pinned never needs telling apart
+                let others: Vec<(usize, usize)> = self
+                    .legal_moves()
+                    .into_iter()
+                    .filter(|other| {
+                        other.to == m.to
+                            && other.from != m.from
+                            && matches!(self.board.position[other.from], Some(p) if p.piece == piece)
+                    })
+                    .map(|other| Board::i_to_c(other.from))
+                    .collect();
+                let from = Board::i_to_a(m.from);
+                if !others.is_empty() {
+                    if others.iter().all(|&(x, _)| x != from_x) {
+                        san.push_str(&from[..1]);
+                    } else if others.iter().all(|&(_, y)| y != from_y) {
+                        san.push_str(&from[1..]);
+                    } else {
+                        san.push_str(&from);
+                    }
+                }
+            }
+            if capture {
+                san.push('x');
+            }
+            san.push_str(&Board::i_to_a(m.to));
+            if let Some(promotion) = m.promotion {
+                san.push('=');
+                san.push(promotion.as_char());
+            }
+            san
+        };
+
+        // Check and mate are only known once the move has been played
+        let mut next = self.clone();
+        next.make_legal_move(m);
+        match next.status() {
+            GameStatus::Checkmate(_) => san.push('#'),
+            _ if next.is_in_check() => san.push('+'),
+            _ => {}
+        }
+        Ok(san)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game::{Color, Game, Move, PieceType, STARTING_FEN};
+
+    fn san(fen: &str, uci: &str) -> String {
+        let game = Game::from_fen(fen).unwrap();
+        game.move_to_san(Move::from_uci(uci).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn san_marks_checks_and_mates() {
+        assert_eq!(san(STARTING_FEN, "g1f3"), "Nf3");
+        assert_eq!(san(STARTING_FEN, "e2e4"), "e4");
+        assert_eq!(san("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1", "a1a8"), "Ra8#");
+        assert_eq!(san("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1", "a1a7"), "Ra7");
+        assert_eq!(san("4k3/8/8/8/8/8/8/R3K3 w - - 0 1", "a1a8"), "Ra8+");
+        // Capturing promotion with mate
+        assert_eq!(
+            san("3r2k1/4Pppp/8/8/8/8/8/6K1 w - - 0 1", "e7d8q"),
+            "exd8=Q#"
+        );
+        // Discovered check: the bishop moves off the rook's file
+        assert_eq!(san("4k3/8/8/8/4B3/8/8/K3R3 w - - 0 1", "e4d5"), "Bd5+");
+        assert_eq!(san("r3k3/8/8/8/8/8/8/4K2R w K - 0 1", "e1g1"), "O-O");
+    }
+
+    #[test]
+    fn san_disambiguates_by_file_rank_or_both() {
+        assert_eq!(san("4k3/8/8/8/8/8/4K3/R6R w - - 0 1", "a1d1"), "Rad1");
+        assert_eq!(san("4k3/8/8/R7/8/8/8/R3K3 w - - 0 1", "a1a3"), "R1a3");
+        assert_eq!(san("4k3/8/8/8/Q6Q/8/8/Q3K3 w - - 0 1", "a4d4"), "Qa4d4");
+        assert_eq!(san("4k3/8/8/3p4/4P3/8/8/4K3 w - - 0 1", "e4d5"), "exd5");
+    }
+
+    #[test]
+    fn san_round_trips() {
+        for fen in [
+            STARTING_FEN,
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        ] {
+            let game = Game::from_fen(fen).unwrap();
+            for m in game.legal_moves() {
+                let text = game.move_to_san(m).unwrap();
+                assert_eq!(game.san_to_move(&text).unwrap(), m, "{}", text);
+            }
+        }
+    }
+
+    #[test]
+    fn pinned_pieces_need_no_disambiguation() {
+        // The knight on c3 is pinned by the bishop, so only g1 can go to e2
+        let fen = "4k3/8/8/8/1b6/2N5/8/4K1N1 w - - 0 1";
+        assert_eq!(san(fen, "g1e2"), "Ne2");
+        let game = Game::from_fen(fen).unwrap();
+        assert_eq!(
+            game.san_to_move("Ne2").unwrap(),
+            Move::from_uci("g1e2").unwrap()
+        );
+
+        // Without the pin both knights can get there
+        assert_eq!(san("4k3/8/8/8/8/2N5/8/4K1N1 w - - 0 1", "g1e2"), "Nge2");
+    }
+
+    #[test]
+    fn moves_can_be_made_from_text() {
+        let mut game = Game::from_fen(STARTING_FEN).unwrap();
+        game.make_move_san("e4").unwrap();
+        assert_eq!(game.turn(), Color::Black);
+        assert!(game.board().is_occupied(4, 4));
+        game.make_move_uci("e7e5").unwrap();
+        assert_eq!(game.turn(), Color::White);
+        assert_eq!(game.history().len(), 2);
+
+        // Illegal or unreadable moves leave the game alone
+        assert!(game.make_move_uci("e4e5").is_err());
+        assert!(game.make_move_uci("e4").is_err());
+        assert!(game.make_move_san("Ke3").is_err());
+        assert!(game.make_move_san("").is_err());
+        assert_eq!(game.history().len(), 2);
+    }
+
+    #[test]
+    fn promotion_spellings() {
+        let game = Game::from_fen("8/4P3/8/8/8/8/8/k6K w - - 0 1").unwrap();
+        let queen = Move::from_uci("e7e8q").unwrap();
+        for text in ["e8=Q", "e8Q", "e8=q", "e8q", "e8Q+"] {
+            let m = game.san_to_move(text).unwrap();
+            assert_eq!(m, queen, "{}", text);
+            assert_eq!(m.promotion(), Some(PieceType::Queen));
+        }
+        assert_eq!(game.move_to_san(queen).unwrap(), "e8=Q");
+        assert_eq!(
+            game.san_to_move("e8n").unwrap().promotion(),
+            Some(PieceType::Knight)
+        );
+        assert!(game.san_to_move("e8K").is_err());
+        assert!(game.san_to_move("e8").is_err());
+
+        let game = Game::from_fen("3r4/4P3/8/8/8/8/8/k6K w - - 0 1").unwrap();
+        assert_eq!(
+            game.san_to_move("exd8b").unwrap(),
+            Move::from_uci("e7d8b").unwrap()
+        );
+    }
+}
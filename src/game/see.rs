@@ -0,0 +1,179 @@
+use super::{Board, Color, Game, Move, Piece, PieceType};
+
+impl Board {
+    /// Static exchange evaluation: the material won (positive) or lost
+    /// (negative) by the side making `capture` once both sides have
+    /// recaptured on the target square for as long as it pays off, always
+    /// with their least valuable piece first.
+    ///
+    /// Pins and checks are ignored, and a pawn moving diagonally onto an
+    /// empty square is treated as capturing en passant.
+    pub fn see(&self, capture: Move) -> i32 {
+        let mover = match self.position.get(capture.from).copied().flatten() {
+            Some(piece) if capture.to < self.position.len() => piece,
+            _ => return 0,
+        };
+        let (to_x, to_y) = Self::i_to_c(capture.to);
+        let (from_x, from_y) = Self::i_to_c(capture.from);
+
+        let mut board = self.clone();
+        let mut gains = vec![match self.position[capture.to] {
+            Some(piece) => piece.piece.value(),
+            None if mover.piece == PieceType::Pawn && from_x != to_x => {
+                board.position[Self::c_to_i(to_x, from_y)] = None;
+                PieceType::Pawn.value()
+            }
+            None => 0,
+        }];
+        let mut on_target = mover.piece;
+        if let Some(promotion) = capture.promotion {
+            gains[0] += promotion.value() - PieceType::Pawn.value();
+            on_target = promotion;
+        }
+        board.position[capture.from] = None;
+        board.position[capture.to] = Some(Piece {
+            piece: on_target,
+            color: mover.color,
+        });
+
+        let mut side = mover.color.opposite();
+        while let Some((x, y)) = board.least_valuable_attacker(to_x, to_y, side) {
+            let attacker = match board.get(x, y) {
+                Some(piece) => piece,
+                None => break,
+            };
+            // A king can only recapture if nothing defends the square
+            if attacker.piece == PieceType::King
+                && board
+                    .least_valuable_attacker(to_x, to_y, side.opposite())
+                    .is_some()
+            {
+                break;
+            }
+            let last = gains[gains.len() - 1];
+            gains.push(on_target.value() - last);
+            on_target = attacker.piece;
+            board.position[Self::c_to_i(x, y)] = None;
+            board.position[capture.to] = Some(attacker);
+            side = side.opposite();
+        }
+
+        // Each side may stop recapturing when continuing would lose more
+        while gains.len() > 1 {
+            let last = gains.pop().unwrap_or(0);
+            let previous = gains.len() - 1;
+            gains[previous] = -(-gains[previous]).max(last);
+        }
+        gains[0]
+    }
+
+    /// Finds the cheapest piece of `color` that can capture on `(x, y)`
+    /// right now, skipping pieces further back in a battery.
+    fn least_valuable_attacker(&self, x: usize, y: usize, color: Color) -> Option<(usize, usize)> {
+        self.attackers_of(x, y, color)
+            .into_iter()
+            .filter(|&(ax, ay)| self.line_is_clear(ax, ay, x, y))
+            .min_by_key(|&(ax, ay)| match self.get(ax, ay) {
+                Some(piece) => piece.piece.value(),
+                None => i32::MAX,
+            })
+    }
+
+    /// Checks that every square strictly between two coordinates on the
+    /// same line is empty. Coordinates that are not on a line, like a
+    /// knight's jump, are always clear.
+    fn line_is_clear(&self, x1: usize, y1: usize, x2: usize, y2: usize) -> bool {
+        let (dx, dy) = (x2 as i32 - x1 as i32, y2 as i32 - y1 as i32);
+        if dx != 0 && dy != 0 && dx.abs() != dy.abs() {
+            return true;
+        }
+        let (step_x, step_y) = (dx.signum(), dy.signum());
+        let (mut x, mut y) = (x1, y1);
+        while let Some((nx, ny)) = Self::offset(x, y, step_x, step_y) {
+            if (nx, ny) == (x2, y2) {
+                return true;
+            }
+            if self.is_occupied(nx, ny) {
+                return false;
+            }
+            (x, y) = (nx, ny);
+        }
+        true
+    }
+}
+
+impl Game {
+    /// Checks whether playing `m` leaves one of the mover's pieces
+    /// hanging: the opponent could then capture it and come out ahead
+    /// by [`Board::see`]. Returns the coordinate of the piece that would
+    /// lose the most, or [`None`] if nothing hangs or the move is not
+    /// legal. Only captures straight after the move are looked at.
+    pub fn is_hanging_after(&self, m: &Move) -> Option<(usize, usize)> {
+        let m = self.generated(*m)?;
+        let mut next = self.clone();
+        next.make_legal_move(m);
+        next.legal_moves()
+            .into_iter()
+            .filter(|reply| {
+                matches!(next.board.position[reply.to], Some(piece) if piece.color == self.turn)
+            })
+            .map(|reply| (next.board.see(reply), reply.to))
+            .filter(|&(gain, _)| gain > 0)
+            .max_by_key(|&(gain, _)| gain)
+            .map(|(_, to)| Board::i_to_c(to))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game::{Game, Move};
+
+    fn see(fen: &str, capture: &str) -> i32 {
+        let game = Game::from_fen(fen).unwrap();
+        game.board.see(Move::from_uci(capture).unwrap())
+    }
+
+    #[test]
+    fn undefended_captures_win_the_piece() {
+        assert_eq!(see("4k3/8/8/3p4/8/4N3/8/4K3 w - - 0 1", "e3d5"), 100);
+        assert_eq!(see("8/8/3k4/8/8/4p3/4K3/8 w - - 0 1", "e2e3"), 100);
+    }
+
+    #[test]
+    fn defended_captures_count_the_recapture() {
+        // Knight takes a pawn defended by a pawn
+        assert_eq!(see("4k3/8/2p5/3p4/8/4N3/8/4K3 w - - 0 1", "e3d5"), -200);
+        // Bishop takes a knight defended by a pawn
+        assert_eq!(see("4k3/8/2p5/3n4/8/8/6B1/4K3 w - - 0 1", "g2d5"), 0);
+        // Rook takes a rook, both backed by a second rook
+        assert_eq!(see("3rk3/3r4/8/8/8/8/3R4/3RK3 w - - 0 1", "d2d7"), 0);
+        // Queen takes a pawn defended by the king
+        assert_eq!(see("8/8/3k4/3p4/8/8/8/3QK3 w - - 0 1", "d1d5"), -800);
+    }
+
+    #[test]
+    fn out_of_range_captures_are_worth_nothing() {
+        let game = Game::from_fen("4k3/8/8/3p4/8/4N3/8/4K3 w - - 0 1").unwrap();
+        assert_eq!(game.board.see(Move::new(70, 0, None)), 0);
+        assert_eq!(game.board.see(Move::new(44, 70, None)), 0);
+    }
+
+    #[test]
+    fn hanging_after_move() {
+        let game = Game::from_fen("rnbqkbnr/pppp1ppp/8/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 0 2")
+            .unwrap();
+        let hanging = |uci: &str| game.is_hanging_after(&Move::from_uci(uci).unwrap());
+        // Nd4 walks into exd4; the other moves leave nothing en prise.
+        assert_eq!(hanging("f3d4"), Some((3, 4)));
+        assert_eq!(hanging("b1c3"), None);
+        assert_eq!(hanging("f1c4"), None);
+        assert_eq!(hanging("e1e3"), None);
+
+        // A defended knight that only the queen can take is not hanging.
+        let game = Game::from_fen("4k3/8/7q/8/8/8/3P4/4K1N1 w - - 0 1").unwrap();
+        assert_eq!(
+            game.is_hanging_after(&Move::from_uci("g1e3").unwrap()),
+            None
+        );
+    }
+}
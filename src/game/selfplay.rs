@@ -0,0 +1,53 @@
+use super::{zobrist::XorShift, Game, GameStatus, STARTING_FEN};
+
+impl Game {
+    /// Plays a game of random legal moves from the starting position until
+    /// it is over. The same seed always plays the same game. Every game
+    /// ends, at the latest by the seventy-five move rule, which makes this
+    /// a quick way to exercise the whole rule set.
+    pub fn play_random_game(seed: u64) -> Game {
+        let mut rng = XorShift::new(seed);
+        let mut game = Game::from_fen(STARTING_FEN).expect("the starting position is valid");
+        while game.status() == GameStatus::Ongoing {
+            let moves = game.legal_moves();
+            let m = moves[(rng.next() % moves.len() as u64) as usize];
+            game.make_legal_move(m);
+        }
+        game
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game::{Game, GameResult, GameStatus};
+
+    #[test]
+    fn random_games_end_and_repeat_by_seed() {
+        // The seventy-five move rule ends a game 150 plies after the last
+        // pawn move or capture, and there can only be 96 pawn moves and 30
+        // captures in a game
+        let max_plies = (96 + 30 + 1) * 150;
+        for seed in 0..200 {
+            let game = Game::play_random_game(seed);
+            assert!(game.ply() < max_plies, "seed {}", seed);
+            // Nobody claims a draw, so the game has to have ended by itself
+            let status = game.status();
+            assert_ne!(status.result(), GameResult::Ongoing, "seed {}", seed);
+            assert!(
+                !matches!(status, GameStatus::DrawClaimed(_)),
+                "seed {}",
+                seed
+            );
+        }
+        for seed in 0..5 {
+            assert_eq!(
+                Game::play_random_game(seed).history(),
+                Game::play_random_game(seed).history()
+            );
+        }
+        assert_ne!(
+            Game::play_random_game(1).history(),
+            Game::play_random_game(2).history()
+        );
+    }
+}
@@ -0,0 +1,567 @@
+use super::{Board, Color, Game, PieceType};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+/// Whether a game is still going, and if not, why it ended
+pub enum GameStatus {
+    Ongoing,
+    /// The given color won by checkmate.
+    Checkmate(Color),
+    Stalemate,
+    /// Neither side has enough material left to checkmate.
+    InsufficientMaterial,
+    /// No sequence of legal moves can end in checkmate, even though there
+    /// is enough material on the board.
+    DeadPosition,
+    /// The same position occurred five times.
+    FivefoldRepetition,
+    /// Seventy-five moves by each player passed without a pawn move or
+    /// capture.
+    SeventyFiveMoveRule,
+    /// A player claimed a draw with [`Game::claim_draw`].
+    DrawClaimed(DrawClaim),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+/// A draw that only ends the game if a player claims it
+pub enum DrawClaim {
+    /// The same position occurred three times.
+    ThreefoldRepetition,
+    /// Fifty moves by each player passed without a pawn move or capture.
+    FiftyMoveRule,
+}
+
+impl GameStatus {
+    /// The result of the game this status belongs to.
+    pub fn result(self) -> GameResult {
+        match self {
+            GameStatus::Ongoing => GameResult::Ongoing,
+            GameStatus::Checkmate(Color::White) => GameResult::WhiteWins,
+            GameStatus::Checkmate(Color::Black) => GameResult::BlackWins,
+            GameStatus::Stalemate
+            | GameStatus::InsufficientMaterial
+            | GameStatus::DeadPosition
+            | GameStatus::FivefoldRepetition
+            | GameStatus::SeventyFiveMoveRule
+            | GameStatus::DrawClaimed(_) => GameResult::Draw,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+/// The outcome of a game, as recorded in PGN
+pub enum GameResult {
+    WhiteWins,
+    BlackWins,
+    Draw,
+    Ongoing,
+}
+
+impl GameResult {
+    /// The result token used at the end of PGN movetext and in the
+    /// `Result` tag.
+    pub fn to_pgn_token(self) -> &'static str {
+        match self {
+            GameResult::WhiteWins => "1-0",
+            GameResult::BlackWins => "0-1",
+            GameResult::Draw => "1/2-1/2",
+            GameResult::Ongoing => "*",
+        }
+    }
+}
+
+impl Board {
+    /// Checks for the standard insufficient material draws: king against
+    /// king with at most one knight or bishop, or any number of bishops
+    /// that all stand on squares of the same color.
+    pub fn is_insufficient_material(&self) -> bool {
+        let mut minors = 0;
+        let mut knights = 0;
+        let (mut light_bishops, mut dark_bishops) = (false, false);
+        for (index, piece) in self.position.iter().enumerate() {
+            let piece = match piece {
+                Some(piece) => piece,
+                None => continue,
+            };
+            match piece.piece {
+                PieceType::King => {}
+                PieceType::Knight => {
+                    minors += 1;
+                    knights += 1;
+                }
+                PieceType::Bishop => {
+                    let (x, y) = Self::i_to_c(index);
+                    minors += 1;
+                    match Self::square_color(x, y) {
+                        Color::White => light_bishops = true,
+                        Color::Black => dark_bishops = true,
+                    }
+                }
+                PieceType::Pawn | PieceType::Rook | PieceType::Queen => return false,
+            }
+        }
+        minors <= 1 || (knights == 0 && !(light_bishops && dark_bishops))
+    }
+
+    /// Whether the pieces of `color` are enough to force checkmate, as
+    /// described on [`Game::can_force_mate_theoretically`].
+    fn can_force_mate(&self, color: Color) -> bool {
+        let mut knights = 0;
+        let (mut light_bishops, mut dark_bishops) = (false, false);
+        let mut defender_has_pawns = false;
+        for (index, piece) in self.pieces() {
+            if piece.color != color {
+                defender_has_pawns |= piece.piece == PieceType::Pawn;
+                continue;
+            }
+            match piece.piece {
+                PieceType::King => {}
+                PieceType::Pawn | PieceType::Rook | PieceType::Queen => return true,
+                PieceType::Knight => knights += 1,
+                PieceType::Bishop => {
+                    let (x, y) = Self::i_to_c(index);
+                    match Self::square_color(x, y) {
+                        Color::White => light_bishops = true,
+                        Color::Black => dark_bishops = true,
+                    }
+                }
+            }
+        }
+        let bishops = light_bishops || dark_bishops;
+        (light_bishops && dark_bishops)
+            || (bishops && knights > 0)
+            || knights > 2
+            || (knights == 2 && defender_has_pawns)
+    }
+
+    /// The pieces of `color` as letters, king first and then from most to
+    /// least valuable, joined like `K+R+P`.
+    fn material_string(&self, color: Color) -> String {
+        let order = [
+            PieceType::King,
+            PieceType::Queen,
+            PieceType::Rook,
+            PieceType::Bishop,
+            PieceType::Knight,
+            PieceType::Pawn,
+        ];
+        let mut letters = vec![];
+        for piece in order {
+            for p in self.position.iter().flatten() {
+                if p.piece == piece && p.color == color {
+                    letters.push(piece.as_char().to_string());
+                }
+            }
+        }
+        letters.join("+")
+    }
+}
+
+impl Game {
+    /// Works out whether the game is over, and how.
+    pub fn status(&self) -> GameStatus {
+        if let Some(claim) = self.draw_claim {
+            GameStatus::DrawClaimed(claim)
+        } else if !self.has_legal_move() {
+            if self.is_in_check() {
+                GameStatus::Checkmate(self.turn.opposite())
+            } else {
+                GameStatus::Stalemate
+            }
+        } else if self.repetition_count() >= 5 {
+            GameStatus::FivefoldRepetition
+        } else if self.fifty_move_rule >= 150 {
+            GameStatus::SeventyFiveMoveRule
+        } else if self.board.is_insufficient_material() {
+            GameStatus::InsufficientMaterial
+        } else if self.is_dead_position() {
+            GameStatus::DeadPosition
+        } else {
+            GameStatus::Ongoing
+        }
+    }
+
+    /// Explains in words why the game has ended, such as `"Checkmate:
+    /// black king on g8 attacked by queen on g7, no escape"`. Returns
+    /// [`None`] while the game is still going.
+    pub fn status_reason(&self) -> Option<String> {
+        let reason = match self.status() {
+            GameStatus::Ongoing => return None,
+            GameStatus::Checkmate(_) => {
+                let (x, y) = self.board.king(self.turn)?;
+                let checkers: Vec<String> = self
+                    .board
+                    .checkers(self.turn)
+                    .into_iter()
+                    .filter_map(|index| {
+                        let piece = self.board.position.get(index).copied().flatten()?;
+                        Some(format!(
+                            "{} on {}",
+                            piece.piece.name(),
+                            Board::i_to_a(index)
+                        ))
+                    })
+                    .collect();
+                format!(
+                    "Checkmate: {} king on {} attacked by {}, no escape",
+                    self.turn.name(),
+                    Board::i_to_a(Board::c_to_i(x, y)),
+                    checkers.join(" and ")
+                )
+            }
+            GameStatus::Stalemate => format!(
+                "Stalemate: {} is not in check but has no legal moves",
+                self.turn.name()
+            ),
+            GameStatus::InsufficientMaterial => format!(
+                "Draw by insufficient material: {} vs {}",
+                self.board.material_string(Color::White),
+                self.board.material_string(Color::Black)
+            ),
+            GameStatus::DeadPosition => {
+                "Draw by dead position: the pawns are locked and neither king can get at them"
+                    .to_owned()
+            }
+            GameStatus::FivefoldRepetition => {
+                "Draw by fivefold repetition: the same position occurred five times".to_owned()
+            }
+            GameStatus::SeventyFiveMoveRule => {
+                "Draw by the seventy-five move rule: 75 moves each without a pawn move or capture"
+                    .to_owned()
+            }
+            GameStatus::DrawClaimed(DrawClaim::ThreefoldRepetition) => {
+                "Draw claimed by threefold repetition: the same position occurred three times"
+                    .to_owned()
+            }
+            GameStatus::DrawClaimed(DrawClaim::FiftyMoveRule) => {
+                "Draw claimed by the fifty move rule: 50 moves each without a pawn move or capture"
+                    .to_owned()
+            }
+        };
+        Some(reason)
+    }
+
+    /// The result of the game so far.
+    pub fn result(&self) -> GameResult {
+        self.status().result()
+    }
+
+    /// Checks for the draws that end the game without either player
+    /// claiming them: fivefold repetition and the seventy-five move rule.
+    /// Unlike threefold repetition and the fifty move rule, these apply
+    /// even if nobody asks.
+    pub fn is_automatic_draw(&self) -> bool {
+        matches!(
+            self.status(),
+            GameStatus::FivefoldRepetition | GameStatus::SeventyFiveMoveRule
+        )
+    }
+
+    /// The draw the player to move could claim right now, if any. A
+    /// threefold repetition is offered ahead of the fifty move rule when
+    /// both apply.
+    pub fn can_claim_draw(&self) -> Option<DrawClaim> {
+        [DrawClaim::ThreefoldRepetition, DrawClaim::FiftyMoveRule]
+            .into_iter()
+            .find(|&claim| self.is_valid_claim(claim))
+    }
+
+    /// Ends the game in a draw if `claim` is valid right now.
+    pub fn claim_draw(&mut self, claim: DrawClaim) -> Result<(), String> {
+        if !self.is_valid_claim(claim) {
+            return Err(format!("Cannot claim a draw by {:?}", claim));
+        }
+        self.draw_claim = Some(claim);
+        Ok(())
+    }
+
+    /// Checks that the game is still going and `claim` applies to the
+    /// current position.
+    fn is_valid_claim(&self, claim: DrawClaim) -> bool {
+        let applies = match claim {
+            DrawClaim::ThreefoldRepetition => self.is_threefold_repetition(),
+            DrawClaim::FiftyMoveRule => self.fifty_move_rule >= 100,
+        };
+        applies && self.status() == GameStatus::Ongoing
+    }
+
+    /// Checks whether the player to move has been checkmated.
+    pub fn is_checkmate(&self) -> bool {
+        self.is_in_check() && !self.has_legal_move()
+    }
+
+    /// Checks whether the player to move has no legal moves but is not in
+    /// check.
+    pub fn is_stalemate(&self) -> bool {
+        !self.is_in_check() && !self.has_legal_move()
+    }
+
+    /// Checks for a "dead position", where no sequence of legal moves can
+    /// end in checkmate. This covers insufficient material and positions
+    /// where only kings and pawns remain and:
+    ///
+    /// - every pawn is blocked by another pawn and has nothing to capture,
+    /// - no king can reach a square next to an enemy pawn.
+    ///
+    /// Since the pawns can never move again, no piece can ever give check.
+    /// This is not exhaustive: fortresses involving other pieces, or where
+    /// a king can reach a pawn that is defended, are not detected.
+    pub fn is_dead_position(&self) -> bool {
+        if self.board.is_insufficient_material() {
+            return true;
+        }
+
+        let board = &self.board;
+        for (index, piece) in board.position.iter().enumerate() {
+            match piece {
+                Some(piece) if piece.piece == PieceType::Pawn => {
+                    let (x, y) = Board::i_to_c(index);
+                    match Board::offset(x, y, 0, piece.color.pawn_direction())
+                        .and_then(|(fx, fy)| board.get(fx, fy))
+                    {
+                        Some(blocker) if blocker.piece == PieceType::Pawn => {}
+                        _ => return false,
+                    }
+                }
+                Some(piece) if piece.piece == PieceType::King => {}
+                Some(_) => return false,
+                None => {}
+            }
+        }
+
+        // With every pawn blocked, only a capture (possibly en passant)
+        // could free one up
+        if self
+            .legal_moves()
+            .iter()
+            .any(|m| matches!(board.position[m.from], Some(p) if p.piece == PieceType::Pawn))
+        {
+            return false;
+        }
+
+        [Color::White, Color::Black]
+            .into_iter()
+            .all(|color| !self.king_reaches_pawn(color))
+    }
+
+    /// A best guess at whether either player has the material to force
+    /// checkmate against any defence, from the pieces alone. This is a
+    /// heuristic, not a proof: it ignores where the pieces stand, so a
+    /// position where the material wins might still be drawn, and the
+    /// reverse.
+    ///
+    /// A pawn, a rook or queen, bishops on both colors, or a bishop and a
+    /// knight are enough. Two knights are legally enough to mate, so this
+    /// is not an insufficient material draw, but they cannot force it
+    /// against a lone king. They can sometimes win if the defender has
+    /// pawns of their own, so that counts as enough.
+    pub fn can_force_mate_theoretically(&self) -> bool {
+        [Color::White, Color::Black]
+            .into_iter()
+            .any(|color| self.board.can_force_mate(color))
+    }
+
+    /// Flood fills the squares the king of `color` can walk to without
+    /// stepping onto a pawn or into a pawn's attack, and checks whether any
+    /// of them is next to an enemy pawn.
+    fn king_reaches_pawn(&self, color: Color) -> bool {
+        let board = &self.board;
+        let start = match board.king(color) {
+            Some(start) => start,
+            None => return false,
+        };
+        let enemy = color.opposite();
+        let mut seen = [false; 64];
+        let mut stack = vec![start];
+        seen[Board::c_to_i(start.0, start.1)] = true;
+        while let Some((x, y)) = stack.pop() {
+            for (dx, dy) in super::movegen::KING_STEPS {
+                let (nx, ny) = match Board::offset(x, y, dx, dy) {
+                    Some(next) => next,
+                    None => continue,
+                };
+                if board.has(nx, ny, PieceType::Pawn, enemy) {
+                    return true;
+                }
+                let index = Board::c_to_i(nx, ny);
+                if seen[index]
+                    || board.has(nx, ny, PieceType::Pawn, color)
+                    || Self::pawn_attacks(board, nx, ny, enemy)
+                {
+                    continue;
+                }
+                seen[index] = true;
+                stack.push((nx, ny));
+            }
+        }
+        false
+    }
+
+    /// Checks whether a pawn of color `by` attacks `(x, y)`.
+    fn pawn_attacks(board: &Board, x: usize, y: usize, by: Color) -> bool {
+        let dy = -by.pawn_direction();
+        [-1, 1].into_iter().any(|dx| {
+            matches!(Board::offset(x, y, dx, dy), Some((px, py)) if board.has(px, py, PieceType::Pawn, by))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{GameResult, GameStatus};
+    use crate::game::{Color, DrawClaim, Game, Move, STARTING_FEN};
+
+    fn is_dead(fen: &str) -> bool {
+        Game::from_fen(fen).unwrap().is_dead_position()
+    }
+
+    #[test]
+    fn locked_pawns_are_dead() {
+        assert!(is_dead("k7/8/8/p1p1p1p1/P1P1P1P1/8/8/K7 w - - 0 1"));
+        assert!(is_dead("8/8/4k3/p1p1p1p1/P1P1P1P1/8/4K3/8 w - - 0 1"));
+        // The kings can get at the pawns on the open side
+        assert!(!is_dead("8/8/4k3/p1p1p3/P1P1P3/8/4K3/8 w - - 0 1"));
+        // The h-pawn can still move
+        assert!(!is_dead("8/8/4k3/p1p1p1pp/P1P1P1P1/8/4K3/8 w - - 0 1"));
+    }
+
+    #[test]
+    fn insufficient_material_is_dead() {
+        assert!(is_dead("8/8/4k3/8/8/8/4K3/8 w - - 0 1"));
+        assert!(is_dead("8/8/4k3/8/8/8/2B1K3/8 w - - 0 1"));
+        assert!(!is_dead("8/8/4k3/8/8/8/2N1K1N1/8 w - - 0 1"));
+    }
+
+    #[test]
+    fn mate_and_stalemate_use_legal_move_check() {
+        let game = Game::from_fen("rnb1kbnr/pppp1ppp/8/4p3/6Pq/5P2/PPPPP2P/RNBQKBNR w KQkq - 1 3")
+            .unwrap();
+        assert!(!game.has_legal_move());
+        assert!(game.is_checkmate() && !game.is_stalemate());
+
+        let game = Game::from_fen("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1").unwrap();
+        assert!(!game.has_legal_move());
+        assert!(game.is_stalemate() && !game.is_checkmate());
+
+        assert!(Game::from_fen(STARTING_FEN).unwrap().has_legal_move());
+    }
+
+    #[test]
+    fn results_map_to_pgn_tokens() {
+        let game = Game::from_fen("rnb1kbnr/pppp1ppp/8/4p3/6Pq/5P2/PPPPP2P/RNBQKBNR w KQkq - 1 3")
+            .unwrap();
+        assert_eq!(game.status(), GameStatus::Checkmate(Color::Black));
+        assert_eq!(game.result(), GameResult::BlackWins);
+        assert_eq!(game.result().to_pgn_token(), "0-1");
+
+        let game = Game::from_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1").unwrap();
+        let game = game.with_move(Move::from_uci("a1a8").unwrap()).unwrap();
+        assert_eq!(game.result().to_pgn_token(), "1-0");
+
+        let game = Game::from_fen("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1").unwrap();
+        assert_eq!(game.result(), GameResult::Draw);
+        assert_eq!(game.result().to_pgn_token(), "1/2-1/2");
+
+        let game = Game::from_fen(STARTING_FEN).unwrap();
+        assert_eq!(game.result().to_pgn_token(), "*");
+    }
+
+    #[test]
+    fn fivefold_repetition_and_seventy_five_moves_end_the_game() {
+        let mut game = Game::from_fen(STARTING_FEN).unwrap();
+        let shuffle = ["g1f3", "g8f6", "f3g1", "f6g8"].map(|uci| Move::from_uci(uci).unwrap());
+        for _ in 0..3 {
+            for m in shuffle {
+                game.make_move(m).unwrap();
+            }
+        }
+        assert!(!game.is_automatic_draw());
+        for m in shuffle {
+            game.make_move(m).unwrap();
+        }
+        assert_eq!(game.repetition_count(), 5);
+        assert!(game.is_automatic_draw());
+        assert_eq!(game.status(), GameStatus::FivefoldRepetition);
+        assert!(game.make_move(shuffle[0]).is_err());
+
+        let game = Game::from_fen("4k3/8/8/8/8/8/8/R3K3 w - - 149 100").unwrap();
+        assert_eq!(game.status(), GameStatus::Ongoing);
+        let game = Game::from_fen("4k3/8/8/8/8/8/8/R3K3 w - - 150 100").unwrap();
+        assert_eq!(game.status(), GameStatus::SeventyFiveMoveRule);
+        assert!(game.is_automatic_draw());
+    }
+
+    #[test]
+    fn draws_can_be_claimed() {
+        let mut game = Game::from_fen(STARTING_FEN).unwrap();
+        assert_eq!(game.can_claim_draw(), None);
+        assert!(game.claim_draw(DrawClaim::ThreefoldRepetition).is_err());
+        for _ in 0..2 {
+            for uci in ["g1f3", "g8f6", "f3g1", "f6g8"] {
+                game.make_move(Move::from_uci(uci).unwrap()).unwrap();
+            }
+        }
+        assert_eq!(game.can_claim_draw(), Some(DrawClaim::ThreefoldRepetition));
+        assert!(!game.is_automatic_draw());
+        assert!(game.claim_draw(DrawClaim::FiftyMoveRule).is_err());
+        game.claim_draw(DrawClaim::ThreefoldRepetition).unwrap();
+        assert_eq!(
+            game.status(),
+            GameStatus::DrawClaimed(DrawClaim::ThreefoldRepetition)
+        );
+        assert_eq!(game.result(), GameResult::Draw);
+
+        let game = Game::from_fen("4k3/8/8/8/8/8/8/R3K3 w - - 99 80").unwrap();
+        assert_eq!(game.can_claim_draw(), None);
+        let game = Game::from_fen("4k3/8/8/8/8/8/8/R3K3 w - - 100 80").unwrap();
+        assert_eq!(game.can_claim_draw(), Some(DrawClaim::FiftyMoveRule));
+    }
+
+    fn reason(fen: &str) -> String {
+        Game::from_fen(fen).unwrap().status_reason().unwrap()
+    }
+
+    #[test]
+    fn checkmate_reasons_name_the_checkers() {
+        assert_eq!(
+            reason("6k1/6Q1/6K1/8/8/8/8/8 b - - 0 1"),
+            "Checkmate: black king on g8 attacked by queen on g7, no escape"
+        );
+        // Rook battery: only the front rook gives check
+        assert_eq!(
+            reason("R5k1/R4ppp/8/8/8/8/8/6K1 b - - 0 1"),
+            "Checkmate: black king on g8 attacked by rook on a8, no escape"
+        );
+        assert_eq!(
+            reason("5rkr/5p1p/7N/8/8/8/8/K5R1 b - - 0 1"),
+            "Checkmate: black king on g8 attacked by knight on h6 and rook on g1, no escape"
+        );
+    }
+
+    #[test]
+    fn draw_reasons() {
+        assert_eq!(
+            reason("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1"),
+            "Stalemate: black is not in check but has no legal moves"
+        );
+        assert_eq!(
+            reason("7k/8/6K1/8/8/8/8/2B5 b - - 0 1"),
+            "Draw by insufficient material: K+B vs K"
+        );
+        assert_eq!(Game::from_fen(STARTING_FEN).unwrap().status_reason(), None);
+    }
+
+    #[test]
+    fn force_mate_heuristic() {
+        let can = |fen: &str| Game::from_fen(fen).unwrap().can_force_mate_theoretically();
+        assert!(can("8/8/8/3k4/8/8/8/KR6 w - - 0 1"));
+        assert!(can("8/8/8/3k4/8/8/8/KBN5 w - - 0 1"));
+        assert!(can("8/8/8/3k4/8/8/8/KB2B3 w - - 0 1"));
+        assert!(!can("8/8/8/3k4/8/8/8/KB1B4 w - - 0 1"));
+        assert!(!can("8/8/8/3k4/8/8/8/KN6 w - - 0 1"));
+        assert!(!can("8/8/8/3k4/8/8/8/KNN5 w - - 0 1"));
+        // With a black pawn on the board the knights can mate after all.
+        assert!(can("8/8/8/3kp3/8/8/8/KNN5 w - - 0 1"));
+        assert!(can(STARTING_FEN));
+    }
+}
@@ -0,0 +1,104 @@
+use super::{Board, Color};
+
+/// The width and height of one square in the SVG, in pixels.
+const SQUARE_SIZE: usize = 45;
+
+/// The fill colors of the light and dark squares.
+const LIGHT_SQUARE: &str = "#f0d9b5";
+const DARK_SQUARE: &str = "#b58863";
+
+impl Board {
+    /// Draws the position as a standalone SVG image, with the pieces as
+    /// Unicode symbols and the files and ranks labelled along the edges.
+    /// With `flip` set the board is seen from black's side, with a1 in the
+    /// top right.
+    pub fn to_svg(&self, flip: bool) -> String {
+        let size = 8 * SQUARE_SIZE;
+        let mut svg = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{0}\" height=\"{0}\" viewBox=\"0 0 {0} {0}\">\n",
+            size
+        );
+        for y in 0..8 {
+            for x in 0..8 {
+                // Where the square ends up in the image
+                let (col, row) = if flip { (7 - x, 7 - y) } else { (x, y) };
+                let (left, top) = (col * SQUARE_SIZE, row * SQUARE_SIZE);
+                let fill = match Self::square_color(x, y) {
+                    Color::White => LIGHT_SQUARE,
+                    Color::Black => DARK_SQUARE,
+                };
+                svg.push_str(&format!(
+                    "<rect x=\"{}\" y=\"{}\" width=\"{size}\" height=\"{size}\" fill=\"{}\"/>\n",
+                    left,
+                    top,
+                    fill,
+                    size = SQUARE_SIZE
+                ));
+                if let Some(piece) = self.get(x, y) {
+                    svg.push_str(&format!(
+                        "<text class=\"piece\" x=\"{}\" y=\"{}\" font-size=\"{}\" text-anchor=\"middle\" dominant-baseline=\"central\">{}</text>\n",
+                        left + SQUARE_SIZE / 2,
+                        top + SQUARE_SIZE / 2,
+                        SQUARE_SIZE * 4 / 5,
+                        piece.to_unicode()
+                    ));
+                }
+            }
+        }
+
+        // Files along the bottom edge and ranks along the left
+        for i in 0..8 {
+            let (file, rank) = if flip { (7 - i, i + 1) } else { (i, 8 - i) };
+            svg.push_str(&format!(
+                "<text class=\"coord\" x=\"{}\" y=\"{}\" font-size=\"10\">{}</text>\n",
+                i * SQUARE_SIZE + SQUARE_SIZE - 8,
+                size - 3,
+                (b'a' + file as u8) as char
+            ));
+            svg.push_str(&format!(
+                "<text class=\"coord\" x=\"2\" y=\"{}\" font-size=\"10\">{}</text>\n",
+                i * SQUARE_SIZE + 11,
+                rank
+            ));
+        }
+        svg.push_str("</svg>\n");
+        svg
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game::{Board, STARTING_FEN};
+
+    #[test]
+    fn svg_has_every_square_and_piece() {
+        let board = Board::from_fen(STARTING_FEN).unwrap();
+        let svg = board.to_svg(false);
+        assert!(svg.starts_with("<svg "));
+        assert!(svg.trim_end().ends_with("</svg>"));
+        assert_eq!(svg.matches("<rect ").count(), 64);
+        assert_eq!(svg.matches("class=\"piece\"").count(), 32);
+        assert_eq!(svg.matches("class=\"coord\"").count(), 16);
+        assert_eq!(svg.matches("<text").count(), svg.matches("</text>").count());
+        assert_eq!(
+            Board::blank()
+                .to_svg(false)
+                .matches("class=\"piece\"")
+                .count(),
+            0
+        );
+    }
+
+    #[test]
+    fn flipped_svg_puts_black_at_the_bottom() {
+        let board = Board::from_fen(STARTING_FEN).unwrap();
+        let black_king = |svg: String| {
+            svg.lines()
+                .find(|line| line.contains('♚'))
+                .unwrap()
+                .to_owned()
+        };
+        assert!(black_king(board.to_svg(false)).contains("y=\"22\""));
+        assert!(black_king(board.to_svg(true)).contains("y=\"337\""));
+    }
+}
@@ -0,0 +1,223 @@
+use super::{Game, Move, MoveAnnotations};
+
+#[derive(Clone, Debug)]
+/// A position in a [`GameTree`], along with the move that led to it
+struct Node {
+    /// The game as it stands after `m` was played.
+    game: Game,
+    /// The move that led here. Only the root has none.
+    m: Option<Move>,
+    parent: Option<usize>,
+    /// The clock and evaluation given after `m` in imported PGN.
+    annotations: MoveAnnotations,
+    /// Continuations from this position. The first is the main line and
+    /// the rest are variations.
+    children: Vec<usize>,
+}
+
+#[derive(Clone, Debug)]
+/// A game with branching variations, for analysis and annotated PGN.
+///
+/// Nodes are referred to by index and never removed, so an index stays
+/// valid for the life of the tree. Node `0` is the starting position.
+pub struct GameTree {
+    nodes: Vec<Node>,
+    current: usize,
+}
+
+impl GameTree {
+    /// Starts a tree from the given position.
+    pub fn new(game: Game) -> Self {
+        Self {
+            nodes: vec![Node {
+                game,
+                m: None,
+                parent: None,
+                annotations: MoveAnnotations::default(),
+                children: vec![],
+            }],
+            current: 0,
+        }
+    }
+
+    /// Imports the main line of a PGN game, keeping the `[%clk]` and
+    /// `[%eval]` annotations of each move. Variations are skipped, as in
+    /// [`Game::from_pgn`]. The current node is the end of the game.
+    pub fn from_pgn(pgn: &str) -> Result<Self, String> {
+        let (game, moves) = Game::parse_pgn(pgn)?;
+        let mut tree = Self::new(game);
+        for pgn_move in moves {
+            let m = tree.game().san_to_move(&pgn_move.san)?;
+            let node = tree.add_move(m)?;
+            tree.nodes[node].annotations = pgn_move.annotations();
+        }
+        Ok(tree)
+    }
+
+    /// The node currently being looked at.
+    pub fn current(&self) -> usize {
+        self.current
+    }
+
+    /// The position at the current node.
+    pub fn game(&self) -> &Game {
+        &self.nodes[self.current].game
+    }
+
+    /// The position at the given node.
+    pub fn game_at(&self, node: usize) -> Option<&Game> {
+        self.nodes.get(node).map(|node| &node.game)
+    }
+
+    /// The move that led to the given node.
+    pub fn move_at(&self, node: usize) -> Option<Move> {
+        self.nodes.get(node).and_then(|node| node.m)
+    }
+
+    /// The clock and evaluation annotations of the move that led to the
+    /// given node.
+    pub fn annotations_at(&self, node: usize) -> Option<&MoveAnnotations> {
+        self.nodes.get(node).map(|node| &node.annotations)
+    }
+
+    /// The node before the given one.
+    pub fn parent(&self, node: usize) -> Option<usize> {
+        self.nodes.get(node).and_then(|node| node.parent)
+    }
+
+    /// The continuations after the given node, main line first.
+    pub fn children(&self, node: usize) -> &[usize] {
+        match self.nodes.get(node) {
+            Some(node) => &node.children,
+            None => &[],
+        }
+    }
+
+    /// Jumps to any node in the tree.
+    pub fn go_to(&mut self, node: usize) -> Result<(), String> {
+        if node < self.nodes.len() {
+            self.current = node;
+            Ok(())
+        } else {
+            Err(format!("No such node: {}", node))
+        }
+    }
+
+    /// Steps back to the previous position, returning whether there was
+    /// one.
+    pub fn go_back(&mut self) -> bool {
+        match self.nodes[self.current].parent {
+            Some(parent) => {
+                self.current = parent;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Plays a move from the current node and moves to the result. If the
+    /// move was already played from here, the existing node is reused;
+    /// otherwise it becomes the main line when it is the first
+    /// continuation, and a variation after that.
+    pub fn add_move(&mut self, m: Move) -> Result<usize, String> {
+        let node = self.child(self.current, m)?;
+        self.current = node;
+        Ok(node)
+    }
+
+    /// Plays a move as an alternative to the move that led to the current
+    /// node, and moves to it. At the root this is the same as
+    /// [`GameTree::add_move`].
+    pub fn add_variation(&mut self, m: Move) -> Result<usize, String> {
+        let from = self.nodes[self.current].parent.unwrap_or(self.current);
+        let node = self.child(from, m)?;
+        self.current = node;
+        Ok(node)
+    }
+
+    /// Finds or creates the node reached by playing `m` from `parent`.
+    fn child(&mut self, parent: usize, m: Move) -> Result<usize, String> {
+        if let Some(&existing) = self.nodes[parent]
+            .children
+            .iter()
+            .find(|&&child| self.nodes[child].m == Some(m))
+        {
+            return Ok(existing);
+        }
+        let game = self.nodes[parent].game.with_move(m)?;
+        let node = self.nodes.len();
+        self.nodes.push(Node {
+            game,
+            m: Some(m),
+            parent: Some(parent),
+            annotations: MoveAnnotations::default(),
+            children: vec![],
+        });
+        self.nodes[parent].children.push(node);
+        Ok(node)
+    }
+
+    /// The nodes along the main line, from the root to the end.
+    pub fn mainline(&self) -> Vec<usize> {
+        let mut line = vec![0];
+        while let Some(&next) = self.nodes[line[line.len() - 1]].children.first() {
+            line.push(next);
+        }
+        line
+    }
+
+    /// The moves leading from the root to the given node.
+    pub fn moves_to(&self, node: usize) -> Vec<Move> {
+        let mut moves = vec![];
+        let mut current = Some(node);
+        while let Some(index) = current {
+            let node = match self.nodes.get(index) {
+                Some(node) => node,
+                None => break,
+            };
+            moves.extend(node.m);
+            current = node.parent;
+        }
+        moves.reverse();
+        moves
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::GameTree;
+    use crate::game::{Game, Move, STARTING_FEN};
+
+    #[test]
+    fn variations_branch_off_the_mainline() {
+        let mut tree = GameTree::new(Game::from_fen(STARTING_FEN).unwrap());
+        let e4 = tree.add_move(Move::from_uci("e2e4").unwrap()).unwrap();
+        let e5 = tree.add_move(Move::from_uci("e7e5").unwrap()).unwrap();
+        let c5 = tree.add_variation(Move::from_uci("c7c5").unwrap()).unwrap();
+        assert_eq!(tree.parent(c5), Some(e4));
+        assert_eq!(tree.parent(0), None);
+        assert_eq!(tree.children(e4), &[e5, c5]);
+        assert_eq!(tree.mainline(), vec![0, e4, e5]);
+        assert_eq!(tree.moves_to(c5).len(), 2);
+
+        tree.go_to(e5).unwrap();
+        assert_eq!(
+            tree.game().to_fen(),
+            "rnbqkbnr/pppp1ppp/8/4p3/4P3/8/PPPP1PPP/RNBQKBNR w KQkq e6 0 2"
+        );
+        tree.go_to(c5).unwrap();
+        assert_eq!(
+            tree.game().to_fen(),
+            "rnbqkbnr/pp1ppppp/8/2p5/4P3/8/PPPP1PPP/RNBQKBNR w KQkq c6 0 2"
+        );
+
+        // Playing a move that is already there reuses its node
+        assert_eq!(
+            tree.add_variation(Move::from_uci("e7e5").unwrap()).unwrap(),
+            e5
+        );
+        assert!(tree.go_back());
+        assert_eq!(tree.current(), e4);
+        assert!(tree.add_move(Move::from_uci("e2e4").unwrap()).is_err());
+    }
+}
@@ -0,0 +1,222 @@
+use std::sync::OnceLock;
+
+use super::{CastlingRights, Color, Game, Piece, PieceType};
+
+/// The seed the Zobrist keys are generated from. Position hashes stay the
+/// same across platforms and versions as long as this and the order the
+/// keys are generated in do not change.
+pub const ZOBRIST_SEED: u64 = 0x9E37_79B9_7F4A_7C15;
+
+/// Random keys for each feature of a position. A position's hash is the
+/// XOR of the keys of the features it has.
+struct ZobristKeys {
+    /// For each piece (white pawn to king, then black), for each square.
+    pieces: [[u64; 64]; 12],
+    black_to_move: u64,
+    /// For each castling right, in `KQkq` order.
+    castling: [u64; 4],
+    /// For each file of a capturable en passant square.
+    en_passant: [u64; 8],
+}
+
+impl ZobristKeys {
+    /// Generates the keys from [`ZOBRIST_SEED`], in the order the fields
+    /// are declared.
+    fn generate() -> Self {
+        let mut rng = XorShift(ZOBRIST_SEED);
+        let mut keys = Self {
+            pieces: [[0; 64]; 12],
+            black_to_move: 0,
+            castling: [0; 4],
+            en_passant: [0; 8],
+        };
+        for square_keys in &mut keys.pieces {
+            square_keys.fill_with(|| rng.next());
+        }
+        keys.black_to_move = rng.next();
+        keys.castling.fill_with(|| rng.next());
+        keys.en_passant.fill_with(|| rng.next());
+        keys
+    }
+
+    /// The keys, generated the first time they are needed.
+    fn get() -> &'static Self {
+        static KEYS: OnceLock<ZobristKeys> = OnceLock::new();
+        KEYS.get_or_init(Self::generate)
+    }
+
+    fn piece(&self, piece: Piece, index: usize) -> u64 {
+        let kind = match piece.piece {
+            PieceType::Pawn => 0,
+            PieceType::Knight => 1,
+            PieceType::Bishop => 2,
+            PieceType::Rook => 3,
+            PieceType::Queen => 4,
+            PieceType::King => 5,
+        };
+        let color = match piece.color {
+            Color::White => 0,
+            Color::Black => 6,
+        };
+        self.pieces[color + kind][index]
+    }
+}
+
+/// The xorshift64* pseudorandom number generator.
+pub(super) struct XorShift(u64);
+
+impl XorShift {
+    /// Starts a generator from any seed. The state is never 0, which
+    /// xorshift would be stuck on.
+    pub(super) fn new(seed: u64) -> Self {
+        Self(seed.wrapping_mul(0x2545_F491_4F6C_DD1D).max(1))
+    }
+
+    pub(super) fn next(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+}
+
+/// The part of a position's hash for `piece` standing on the square at
+/// `index`.
+pub(super) fn piece_key(piece: Piece, index: usize) -> u64 {
+    ZobristKeys::get().piece(piece, index)
+}
+
+impl Game {
+    /// The Zobrist hash of the current [`PositionKey`](super::PositionKey),
+    /// used to detect repetitions. It is kept up to date as moves are made,
+    /// so this is cheap; [`Game::position_hash`] works it out from scratch.
+    pub fn hash(&self) -> u64 {
+        self.hash
+    }
+
+    /// Works out the Zobrist hash of the current position from scratch.
+    /// This is always the same as [`Game::hash`], only slower.
+    pub fn position_hash(&self) -> u64 {
+        let mut hash = self.state_hash();
+        for (index, piece) in self.board.position.iter().enumerate() {
+            if let Some(piece) = piece {
+                hash ^= piece_key(*piece, index);
+            }
+        }
+        hash
+    }
+
+    /// The part of the hash for everything but the pieces: the turn,
+    /// castling rights, and en passant square.
+    pub(super) fn state_hash(&self) -> u64 {
+        let keys = ZobristKeys::get();
+        let mut hash = 0;
+        if self.turn == Color::Black {
+            hash ^= keys.black_to_move;
+        }
+        for (i, right) in CastlingRights::EACH.into_iter().enumerate() {
+            if self.castling.contains(right) {
+                hash ^= keys.castling[i];
+            }
+        }
+        if let Some(index) = self.capturable_en_passant() {
+            hash ^= keys.en_passant[index % 8];
+        }
+        hash
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{XorShift, ZobristKeys, ZOBRIST_SEED};
+    use crate::game::{Game, MoveFlags, STARTING_FEN};
+
+    #[test]
+    fn keys_are_fixed_by_the_seed() {
+        let keys = ZobristKeys::get();
+        // The first keys generated, for a white pawn on a8, b8, and c8
+        assert_eq!(
+            keys.pieces[0][..3],
+            [
+                0x0d83_b3e2_9a21_487a,
+                0x54c4_4c79_f1fe_9d67,
+                0xa845_f342_007a_0e78
+            ]
+        );
+        // A white knight on a8, after the 64 pawn keys
+        assert_eq!(keys.pieces[1][0], 0xf45a_4999_063c_350f);
+        assert_eq!(keys.black_to_move, 0x401a_18bb_02d7_d7f0);
+
+        // The move counters are not part of the hash
+        let hash = |fen| Game::from_fen(fen).unwrap().position_hash();
+        assert_eq!(
+            hash("4k3/8/8/8/8/8/8/4K3 w - - 0 1"),
+            hash("4k3/8/8/8/8/8/8/4K3 w - - 12 40")
+        );
+    }
+
+    #[test]
+    fn keys_are_distinct() {
+        let keys = ZobristKeys::get();
+        let mut all: Vec<u64> = keys.pieces.iter().flatten().copied().collect();
+        all.push(keys.black_to_move);
+        all.extend(keys.castling);
+        all.extend(keys.en_passant);
+        let count = all.len();
+        all.sort_unstable();
+        all.dedup();
+        assert_eq!(all.len(), count);
+        assert!(!all.contains(&0));
+    }
+
+    /// Plays random games with passes and take-backs mixed in, checking
+    /// after every step that the hash and material kept up to date match
+    /// working them out from scratch.
+    #[test]
+    fn incremental_updates_match_recomputation() {
+        let mut rng = XorShift::new(ZOBRIST_SEED);
+        let mut random = move || rng.next() as usize;
+        let check = |game: &Game| {
+            assert_eq!(game.hash(), game.position_hash(), "{}", game.to_fen());
+            assert_eq!(
+                game.material_balance(),
+                game.board().material_balance(),
+                "{}",
+                game.to_fen()
+            );
+        };
+        let starts = [
+            STARTING_FEN,
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+            "n1n5/PPPk4/8/8/8/8/4Kppp/5N1N b - - 0 1",
+        ];
+        let (mut castles, mut en_passants, mut promotions, mut captures) = (0, 0, 0, 0);
+        for game_num in 0..200 {
+            let mut game = Game::from_fen(starts[game_num % starts.len()]).unwrap();
+            for _ in 0..80 {
+                let moves = game.legal_moves();
+                if moves.is_empty() {
+                    break;
+                }
+                if random() % 5 == 0 && game.make_null_move().is_ok() {
+                    check(&game);
+                    game.undo_null_move().unwrap();
+                    check(&game);
+                }
+                let m = moves[random() % moves.len()];
+                castles += m.is_castle() as usize;
+                en_passants += m.flags().contains(MoveFlags::EN_PASSANT) as usize;
+                promotions += m.is_promotion() as usize;
+                captures += game.is_capture(&m) as usize;
+                game.make_legal_move(m);
+                check(&game);
+                if random() % 6 == 0 {
+                    game.undo();
+                    check(&game);
+                }
+            }
+        }
+        assert!(castles > 0 && en_passants > 0 && promotions > 0 && captures > 0);
+    }
+}
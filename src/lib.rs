@@ -0,0 +1,6 @@
+//! Chess rules and analysis, independent of the web front end.
+
+pub mod engine;
+pub mod game;
+
+pub use game::{Board, Color, Game, Move, Piece, PieceType};
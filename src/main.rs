@@ -1,116 +1,1326 @@
-use gloo_console::log;
-use web_sys::HtmlElement;
+use chess_rs::engine;
+use chess_rs::game::{
+    Board, Color, Game, GameResult, GameStatus, Move, Piece, PieceType, BOARD_SIZE, STARTING_FEN,
+};
+use gloo_events::EventListener;
+use gloo_timers::callback::Timeout;
+use js_sys::{Function, Promise, Reflect};
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{
+    HtmlElement, HtmlInputElement, HtmlSelectElement, HtmlTextAreaElement, KeyboardEvent,
+};
 use yew::prelude::*;
 
-mod game;
+enum Msg {
+    /// The right mouse button went down over the tile with the
+    /// given index, or somewhere off the board.
+    RightDown(Option<usize>),
+    /// The right mouse button came up over the tile with the
+    /// given index, or somewhere off the board. Toggles a
+    /// highlight if it is the tile it went down on, or else an
+    /// arrow between the two.
+    RightUp(Option<usize>),
+    /// A FEN string typed into the FEN input, to replace the
+    /// current game.
+    LoadFen(String),
+    /// A move in SAN typed into the move input, to play.
+    PlaySan(String),
+    /// A left click on the tile with the given index. Selects
+    /// a piece, or moves the selected piece there, and clears
+    /// the arrows and highlights. A queued premove is cancelled
+    /// instead.
+    TileClick(usize),
+    /// The mouse moved onto the tile with the given index, or
+    /// off the board.
+    Hover(Option<usize>),
+    /// The piece on the tile with the given index was picked
+    /// up to be dragged.
+    DragStart(usize),
+    /// The dragged piece is over the tile with the given index.
+    DragOver(usize),
+    /// The dragged piece was dropped on the tile with the given
+    /// index.
+    Drop(usize),
+    /// Dragging ended, whether or not the piece was dropped on
+    /// the board.
+    DragEnd,
+    /// Show the position one move earlier in the game.
+    Back,
+    /// Show the position one move later in the game.
+    Forward,
+    /// Take back the last move of the game.
+    Undo,
+    /// Turn the board around, which stops it turning by itself.
+    Flip,
+    /// Start or stop turning the board to face whoever is to
+    /// move.
+    AutoFlip(bool),
+    /// Show pieces as letters rather than chess symbols, or go
+    /// back to symbols.
+    Letters(bool),
+    /// Go on to the next way of labelling each tile with where it
+    /// is, for checking which tile a click lands on.
+    ToggleCoords,
+    /// Copy the shown position's FEN to the clipboard.
+    CopyFen,
+    /// Copy the game's PGN to the clipboard.
+    CopyPgn,
+    /// The clipboard accepted the copied text.
+    Copied,
+    /// The clipboard could not be used, so the given text
+    /// should be shown for copying by hand.
+    CopyFailed(String),
+    /// Hide the "Copied!" confirmation.
+    HideCopied,
+    /// Start a new game from the starting position.
+    Reset,
+    /// Let the computer play the given color, or nobody for two
+    /// players sharing the screen. The game goes on from where
+    /// it is.
+    SetComputer(Option<Color>),
+    /// The computer has waited long enough and should move now.
+    ComputerMove,
+}
 
-const BOARD_SIZE: usize = 8;
+/// How many plies ahead the computer looks. The browser has no
+/// clock to stop a timed search, so this stays fixed.
+const COMPUTER_DEPTH: usize = 3;
 
-enum Msg {
-    /// A right click or contextmenu event. Used for
-    /// highlighting the board. Fields are for client
-    /// x and y mouse positions.
-    RightClick(u32, u32),
+/// How long the computer waits before moving, in milliseconds,
+/// so the player's own move can be seen first.
+const COMPUTER_DELAY: u32 = 300;
+
+/// What each tile is labelled with, besides its piece.
+#[derive(Clone, Copy, PartialEq)]
+enum Coords {
+    /// Nothing, as in normal play.
+    Hidden,
+    /// The name of the square, like `e4`.
+    Squares,
+    /// The index of the tile, from 0 for a8 to 63 for h1.
+    Indices,
+}
+
+impl Coords {
+    /// The labelling that comes after this one.
+    fn next(self) -> Self {
+        match self {
+            Coords::Hidden => Coords::Squares,
+            Coords::Squares => Coords::Indices,
+            Coords::Indices => Coords::Hidden,
+        }
+    }
+}
+
+/// The colors of the tiles.
+#[derive(Clone, Copy, PartialEq)]
+enum Theme {
+    Grey,
+    Green,
+    Brown,
+    Blue,
+}
+
+impl Theme {
+    /// Finds a theme by its name, like `"green"`.
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "grey" => Some(Theme::Grey),
+            "green" => Some(Theme::Green),
+            "brown" => Some(Theme::Brown),
+            "blue" => Some(Theme::Blue),
+            _ => None,
+        }
+    }
+
+    /// The class that colors a tile of the given color, like
+    /// `c-green-tile-black`.
+    fn tile_class(self, color: Color) -> String {
+        let theme = match self {
+            Theme::Grey => "grey",
+            Theme::Green => "green",
+            Theme::Brown => "brown",
+            Theme::Blue => "blue",
+        };
+        format!("c-{}-tile-{}", theme, color.name())
+    }
+}
+
+/// How the pieces are drawn.
+#[derive(Clone, Copy, PartialEq)]
+enum PieceSet {
+    /// The chess symbols, like `♔`.
+    Symbols,
+    /// The FEN letters, like `K`, for anyone without a font that
+    /// has the chess symbols.
+    Letters,
+}
+
+impl PieceSet {
+    /// The text a piece is drawn with.
+    fn symbol(self, piece: Piece) -> String {
+        match self {
+            PieceSet::Symbols => piece.to_unicode().to_string(),
+            PieceSet::Letters => piece.to_string(),
+        }
+    }
+}
+
+#[derive(Properties, PartialEq)]
+struct AppProps {
+    /// The FEN of the position to start from. Defaults to the
+    /// standard starting position.
+    #[prop_or(STARTING_FEN.to_owned())]
+    fen: String,
+    /// The name of the tile colors: `grey`, `green`, `brown`, or
+    /// `blue`. Anything else is grey.
+    #[prop_or("grey".to_owned())]
+    theme: String,
+    /// The piece set to draw with. It can still be switched while
+    /// playing.
+    #[prop_or(PieceSet::Symbols)]
+    pieces: PieceSet,
+}
+
+impl Default for AppProps {
+    fn default() -> Self {
+        Self {
+            fen: STARTING_FEN.to_owned(),
+            theme: "grey".to_owned(),
+            pieces: PieceSet::Symbols,
+        }
+    }
 }
 
 struct App {
-    /// A chess board has dimensions of 8 by 8.
-    /// The chess board will be stored in an array, with the
-    /// first 8 elements composing the first row, the second
-    /// 8 elements composing the second row, etc.
-    _board: [u8; BOARD_SIZE * BOARD_SIZE],
+    /// The game being shown on the board.
+    game: Game,
+
+    /// Why the last FEN typed in could not be loaded, until
+    /// one loads successfully.
+    fen_error: Option<String>,
+
+    /// Why the last move typed in could not be played, until
+    /// one is.
+    san_error: Option<String>,
+
+    /// A NodeRef to the move input, so it can be cleared once
+    /// the move is played.
+    san_html: NodeRef,
+
+    /// An earlier position of the game being looked at instead
+    /// of the current one, if any. Moves can only be made in
+    /// the current one.
+    viewing: Option<Game>,
+
+    /// The index of the tile with the piece picked up to be
+    /// moved, and that piece's legal moves.
+    selected: Option<(usize, Vec<Move>)>,
+
+    /// The index of the tile the selected piece is being dragged
+    /// over, while it is being dragged.
+    drag_over: Option<usize>,
+
+    /// The index of the tile under the mouse while nothing is
+    /// selected, and the legal moves of the piece on it.
+    hovered: Option<(usize, Vec<Move>)>,
+
+    /// The tiles highlighted with a right click.
+    highlights: Vec<usize>,
+
+    /// The arrows drawn by dragging with the right mouse button,
+    /// as the indices of the tiles they go from and to.
+    arrows: Vec<(usize, usize)>,
+
+    /// The index of the tile the right mouse button went down
+    /// on, while it is held.
+    right_down: Option<usize>,
+
+    /// Whether the board is shown from black's side, unless
+    /// `auto_flip` decides.
+    flipped: bool,
+
+    /// Whether the board turns to face whoever is to move, for
+    /// two players sharing the screen.
+    auto_flip: bool,
+
+    /// How the pieces are drawn.
+    pieces: PieceSet,
+
+    /// What the tiles are labelled with. Only changed with a
+    /// keyboard shortcut, so it stays out of the way.
+    coords: Coords,
+
+    /// The color the computer plays, or `None` if two people are
+    /// playing.
+    computer: Option<Color>,
+
+    /// Makes the computer move when it runs out. The computer is
+    /// thinking as long as this is set.
+    computer_timeout: Option<Timeout>,
+
+    /// A move chosen while the computer was thinking, to be played
+    /// straight after its reply if it is still legal then.
+    premove: Option<Move>,
 
     /// A NodeRef to the board in the HTML DOM so the board
     /// can pass back mouse coordinates
     board_html: NodeRef,
+
+    /// Text that could not be copied to the clipboard, shown
+    /// so it can be copied by hand.
+    export: Option<String>,
+
+    /// A NodeRef to the text box showing `export`, so its text
+    /// can be selected.
+    export_html: NodeRef,
+
+    /// Whether the text box showing `export` needs its text
+    /// selected after the next render.
+    select_export: bool,
+
+    /// Hides the "Copied!" confirmation when it runs out. The
+    /// confirmation shows as long as this is set.
+    copied_timeout: Option<Timeout>,
+
+    /// Listens for keyboard shortcuts anywhere on the page for as
+    /// long as it is kept.
+    _shortcuts: EventListener,
 }
 
 impl Component for App {
     type Message = Msg;
-    type Properties = ();
+    type Properties = AppProps;
 
-    fn create(_ctx: &Context<Self>) -> Self {
+    fn create(ctx: &Context<Self>) -> Self {
+        // Fall back to the standard starting position so there is
+        // still a board to show
+        let (game, fen_error) = match Game::from_fen(&ctx.props().fen) {
+            Ok(game) => (game, None),
+            Err(e) => (
+                Game::from_fen(STARTING_FEN).unwrap(),
+                Some(format!("Invalid starting position: {}", e)),
+            ),
+        };
         App {
-            _board: [0; 64],
+            game,
+            fen_error,
+            san_error: None,
+            san_html: NodeRef::default(),
+            viewing: None,
+            selected: None,
+            drag_over: None,
+            hovered: None,
+            highlights: vec![],
+            arrows: vec![],
+            right_down: None,
+            flipped: false,
+            auto_flip: false,
+            pieces: ctx.props().pieces,
+            coords: Coords::Hidden,
+            computer: None,
+            computer_timeout: None,
+            premove: None,
             board_html: NodeRef::default(),
+            export: None,
+            export_html: NodeRef::default(),
+            select_export: false,
+            copied_timeout: None,
+            _shortcuts: listen_for_shortcuts(ctx),
         }
     }
 
-    fn update(&mut self, _ctx: &Context<Self>, msg: Self::Message) -> bool {
-        match msg {
-            // TODO: use document().querySelector(".c-tile") instead
-            Msg::RightClick(x, y) => {
-                let style = gloo_utils::document()
-                    .default_view()
-                    .unwrap()
-                    .get_computed_style(&gloo_utils::body())
-                    .unwrap()
-                    .unwrap();
-                let tile_size = style.get_property_value("--c-tile-size").unwrap();
-                log!("Right click", x, y, "; tile size: ", tile_size);
-                // TODO: make highlights
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
+        let changed = match msg {
+            Msg::RightDown(index) => {
+                self.right_down = index;
                 false
             }
+            Msg::RightUp(index) => match (self.right_down.take(), index) {
+                (Some(from), Some(to)) if from == to => {
+                    toggle(&mut self.highlights, to);
+                    true
+                }
+                (Some(from), Some(to)) => {
+                    toggle(&mut self.arrows, (from, to));
+                    true
+                }
+                _ => false,
+            },
+            Msg::LoadFen(fen) => {
+                let fen = fen.trim();
+                if fen.is_empty() {
+                    return false;
+                }
+                match Game::from_fen(fen) {
+                    Ok(game) => self.set_game(game),
+                    Err(e) => self.fen_error = Some(e.to_string()),
+                }
+                true
+            }
+            Msg::PlaySan(san) => {
+                let san = san.trim();
+                if san.is_empty() {
+                    return false;
+                }
+                let played = if self.computer == Some(self.game.turn()) {
+                    Err("Wait for the computer to move".to_owned())
+                } else {
+                    self.game.make_move_san(san)
+                };
+                match played {
+                    Ok(()) => {
+                        self.san_error = None;
+                        self.viewing = None;
+                        self.selected = None;
+                        self.hovered = None;
+                        self.highlights.clear();
+                        self.arrows.clear();
+                        if let Some(input) = self.san_html.cast::<HtmlInputElement>() {
+                            input.set_value("");
+                        }
+                    }
+                    Err(e) => self.san_error = Some(e),
+                }
+                true
+            }
+            Msg::TileClick(_) if self.viewing.is_some() => {
+                // Clicking an earlier position goes back to the current one
+                self.viewing = None;
+                self.highlights.clear();
+                self.arrows.clear();
+                true
+            }
+            Msg::TileClick(_) if self.premove.is_some() => {
+                self.premove = None;
+                true
+            }
+            Msg::TileClick(index) => {
+                self.highlights.clear();
+                self.arrows.clear();
+                let target = self.selected_move(index);
+                let selected = self.selected.take();
+                if let Some(m) = target {
+                    self.play(m);
+                } else if !matches!(selected, Some((from, _)) if from == index) {
+                    // Clicking the selected piece again puts it back down
+                    let moves = self.movable(index);
+                    if !moves.is_empty() {
+                        self.selected = Some((index, moves));
+                    }
+                }
+                self.hovered = None;
+                true
+            }
+            Msg::Hover(index) => {
+                if self.selected.is_some() || self.viewing.is_some() {
+                    return false;
+                }
+                match (index, &self.hovered) {
+                    (Some(index), Some((hovered, _))) if index == *hovered => false,
+                    (Some(index), _) => {
+                        let moves = self.movable(index);
+                        let changed = !moves.is_empty() || self.hovered.is_some();
+                        self.hovered = (!moves.is_empty()).then_some((index, moves));
+                        changed
+                    }
+                    (None, _) => self.hovered.take().is_some(),
+                }
+            }
+            Msg::DragStart(index) => {
+                self.selected = Some((index, self.movable(index)));
+                self.drag_over = Some(index);
+                self.hovered = None;
+                true
+            }
+            // Only pieces dragged from the board are tracked
+            Msg::DragOver(_) | Msg::Drop(_) if self.drag_over.is_none() => false,
+            Msg::DragOver(index) => self.drag_over.replace(index) != Some(index),
+            Msg::Drop(index) => {
+                // Anywhere that is not a legal move puts the piece back
+                if let Some(m) = self.selected_move(index) {
+                    self.play(m);
+                }
+                self.selected = None;
+                self.drag_over = None;
+                true
+            }
+            Msg::DragEnd => {
+                let dragging = self.drag_over.take().is_some();
+                if dragging {
+                    self.selected = None;
+                }
+                dragging
+            }
+            Msg::Back => match self.displayed().ply().checked_sub(1) {
+                Some(ply) => self.view_ply(ply),
+                None => false,
+            },
+            Msg::Forward => {
+                let ply = self.displayed().ply() + 1;
+                ply <= self.game.ply() && self.view_ply(ply)
+            }
+            Msg::Undo => {
+                self.viewing = None;
+                self.selected = None;
+                self.hovered = None;
+                self.computer_timeout = None;
+                self.premove = None;
+                let undone = self.game.undo().is_some();
+                // Take back the computer's reply too, so it is the
+                // player's turn again
+                if self.computer == Some(self.game.turn()) {
+                    self.game.undo();
+                }
+                undone
+            }
+            Msg::Flip => {
+                self.flipped = !self.is_flipped();
+                self.auto_flip = false;
+                true
+            }
+            Msg::AutoFlip(auto_flip) => {
+                self.auto_flip = auto_flip;
+                true
+            }
+            Msg::Letters(letters) => {
+                self.pieces = if letters {
+                    PieceSet::Letters
+                } else {
+                    PieceSet::Symbols
+                };
+                true
+            }
+            Msg::ToggleCoords => {
+                self.coords = self.coords.next();
+                true
+            }
+            Msg::CopyFen => {
+                self.copy(ctx, self.displayed().to_fen());
+                false
+            }
+            Msg::CopyPgn => {
+                self.copy(ctx, self.game.to_pgn());
+                false
+            }
+            Msg::Copied => {
+                self.export = None;
+                let link = ctx.link().clone();
+                self.copied_timeout = Some(Timeout::new(1500, move || {
+                    link.send_message(Msg::HideCopied)
+                }));
+                true
+            }
+            Msg::CopyFailed(text) => {
+                self.export = Some(text);
+                self.select_export = true;
+                true
+            }
+            Msg::HideCopied => self.copied_timeout.take().is_some(),
+            Msg::Reset => {
+                let game = Game::from_fen(&ctx.props().fen)
+                    .unwrap_or_else(|_| Game::from_fen(STARTING_FEN).unwrap());
+                self.set_game(game);
+                true
+            }
+            Msg::SetComputer(computer) => {
+                self.computer = computer;
+                self.computer_timeout = None;
+                self.premove = None;
+                self.selected = None;
+                self.hovered = None;
+                true
+            }
+            Msg::ComputerMove => {
+                self.computer_timeout = None;
+                let reply = engine::search(&self.game, COMPUTER_DEPTH);
+                play_reply(&mut self.game, reply, self.premove.take());
+                true
+            }
+        };
+        self.start_computer(ctx);
+        changed
+    }
+
+    fn rendered(&mut self, _ctx: &Context<Self>, _first_render: bool) {
+        if self.select_export {
+            self.select_export = false;
+            if let Some(export) = self.export_html.cast::<HtmlTextAreaElement>() {
+                export.select();
+            }
         }
     }
 
+    /// The theme is read from the props as the board is drawn, and
+    /// a new piece set replaces the one picked while playing. The
+    /// `fen` prop only sets up the first game, so changing it does
+    /// not throw away the one being played.
+    fn changed(&mut self, ctx: &Context<Self>) -> bool {
+        self.pieces = ctx.props().pieces;
+        true
+    }
+
     fn view(&self, ctx: &Context<Self>) -> Html {
+        // The board handles the mouse for all of its tiles, working
+        // out which one from where the event happened
+        let flipped = self.is_flipped();
+        let board_ref = self.board_html.clone();
+        let onclick = ctx.link().batch_callback(move |e: MouseEvent| {
+            tile_at(&board_ref, &e, flipped).map(Msg::TileClick)
+        });
         let board_ref = self.board_html.clone();
-        let board_onclick = ctx.link().callback(move |e: MouseEvent| {
+        let onmouseover = ctx
+            .link()
+            .callback(move |e: MouseEvent| Msg::Hover(tile_at(&board_ref, &e, flipped)));
+        let onmouseleave = ctx.link().callback(|_| Msg::Hover(None));
+        let board_ref = self.board_html.clone();
+        let ondragstart = ctx.link().batch_callback(move |e: DragEvent| {
+            // Firefox only starts dragging if there is some data
+            if let Some(data) = e.data_transfer() {
+                let _ = data.set_data("text/plain", "");
+            }
+            tile_at(&board_ref, &e, flipped).map(Msg::DragStart)
+        });
+        let board_ref = self.board_html.clone();
+        let ondragover = ctx.link().batch_callback(move |e: DragEvent| {
+            // Allow dropping here
             e.prevent_default();
-            let board = board_ref.cast::<HtmlElement>().unwrap();
-
-            // Get bounding client rect
-            let rect = board.get_bounding_client_rect();
-            let mouse_x = ((e.client_x() as f64) - rect.left()) as u32;
-            let mouse_y = ((e.client_y() as f64) - rect.top()) as u32;
-
-            Msg::RightClick(mouse_x, mouse_y)
+            tile_at(&board_ref, &e, flipped).map(Msg::DragOver)
+        });
+        let board_ref = self.board_html.clone();
+        let ondrop = ctx.link().batch_callback(move |e: DragEvent| {
+            e.prevent_default();
+            tile_at(&board_ref, &e, flipped).map(Msg::Drop)
+        });
+        let ondragend = ctx.link().callback(|_| Msg::DragEnd);
+        let board_ref = self.board_html.clone();
+        let onmousedown = ctx.link().batch_callback(move |e: MouseEvent| {
+            (e.button() == 2).then(|| Msg::RightDown(tile_at(&board_ref, &e, flipped)))
+        });
+        let board_ref = self.board_html.clone();
+        let onmouseup = ctx.link().batch_callback(move |e: MouseEvent| {
+            (e.button() == 2).then(|| Msg::RightUp(tile_at(&board_ref, &e, flipped)))
+        });
+        // Keep the browser's menu from covering the board
+        let oncontextmenu = Callback::from(|e: MouseEvent| e.prevent_default());
+        let fen_onchange = ctx.link().callback(|e: Event| {
+            let input = e.target_unchecked_into::<HtmlInputElement>();
+            Msg::LoadFen(input.value())
+        });
+        let san_onkeydown = ctx.link().batch_callback(|e: KeyboardEvent| {
+            (e.key() == "Enter").then(|| {
+                let input = e.target_unchecked_into::<HtmlInputElement>();
+                Msg::PlaySan(input.value())
+            })
         });
-        let board_html = Self::make_board_html();
+        // Suggest every legal move as it would be written
+        let suggestions = self.game.legal_moves().into_iter().filter_map(|m| {
+            let san = self.game.move_to_san(m).ok()?;
+            Some(html! { <option value={ san } /> })
+        });
+        let theme = Theme::from_name(&ctx.props().theme).unwrap_or(Theme::Grey);
+        let board_html = render_board(
+            self.displayed().board(),
+            theme,
+            flipped,
+            self.pieces,
+            self.coords,
+            &self.highlights(),
+        );
+        let onback = ctx.link().callback(|_| Msg::Back);
+        let onforward = ctx.link().callback(|_| Msg::Forward);
+        let onundo = ctx.link().callback(|_| Msg::Undo);
+        let onflip = ctx.link().callback(|_| Msg::Flip);
+        let onletters = ctx.link().callback(|e: Event| {
+            let input = e.target_unchecked_into::<HtmlInputElement>();
+            Msg::Letters(input.checked())
+        });
+        let oncomputer = ctx.link().callback(|e: Event| {
+            let select = e.target_unchecked_into::<HtmlSelectElement>();
+            Msg::SetComputer(select.value().parse().ok())
+        });
+        let computer = self.computer.map(Color::to_fen_char);
+        let onautoflip = ctx.link().callback(|e: Event| {
+            let input = e.target_unchecked_into::<HtmlInputElement>();
+            Msg::AutoFlip(input.checked())
+        });
+        let oncopyfen = ctx.link().callback(|_| Msg::CopyFen);
+        let oncopypgn = ctx.link().callback(|_| Msg::CopyPgn);
         html! {
-            <div
-                ref={ self.board_html.clone() }
-                oncontextmenu={ board_onclick }
-                class="c-container"
-            >
-                { board_html }
+            <div class="c-app">
+                <div class="c-board-area">
+                    <div
+                        ref={ self.board_html.clone() }
+                        { onclick }
+                        { onmouseover }
+                        { onmouseleave }
+                        { ondragstart }
+                        { ondragover }
+                        { ondrop }
+                        { ondragend }
+                        { onmousedown }
+                        { onmouseup }
+                        { oncontextmenu }
+                        class="c-container"
+                    >
+                        { board_html }
+                        { self.make_arrows_html() }
+                    </div>
+                    { self.make_game_over_html(ctx) }
+                </div>
+                <div class="c-controls">
+                    <button onclick={ onback }>{ "<" }</button>
+                    <button onclick={ onforward }>{ ">" }</button>
+                    <button onclick={ onundo } disabled={ !self.game.can_undo() }>
+                        { "Undo" }
+                    </button>
+                    <button onclick={ onflip }>{ "Flip" }</button>
+                    <label class="c-option">
+                        <input
+                            type="checkbox"
+                            checked={ self.auto_flip }
+                            onchange={ onautoflip }
+                        />
+                        { "Auto flip" }
+                    </label>
+                    <label class="c-option">
+                        <input
+                            type="checkbox"
+                            checked={ self.pieces == PieceSet::Letters }
+                            onchange={ onletters }
+                        />
+                        { "Letters" }
+                    </label>
+                    <select onchange={ oncomputer }>
+                        <option value="" selected={ computer.is_none() }>
+                            { "Two players" }
+                        </option>
+                        <option value="b" selected={ computer == Some('b') }>
+                            { "Play white vs computer" }
+                        </option>
+                        <option value="w" selected={ computer == Some('w') }>
+                            { "Play black vs computer" }
+                        </option>
+                    </select>
+                    if self.computer_timeout.is_some() {
+                        <span class="c-thinking">{ "Thinking..." }</span>
+                    }
+                    <button onclick={ oncopyfen }>{ "Copy FEN" }</button>
+                    <button onclick={ oncopypgn }>{ "Copy PGN" }</button>
+                    if self.copied_timeout.is_some() {
+                        <span class="c-copied">{ "Copied!" }</span>
+                    }
+                </div>
+                if let Some(export) = &self.export {
+                    <textarea
+                        ref={ self.export_html.clone() }
+                        class="c-export"
+                        readonly=true
+                        value={ export.clone() }
+                    />
+                }
+                <input
+                    ref={ self.san_html.clone() }
+                    class="c-san-input"
+                    placeholder="Type a move, like Nf3"
+                    list="c-san-moves"
+                    onkeydown={ san_onkeydown }
+                />
+                <datalist id="c-san-moves">{ for suggestions }</datalist>
+                if let Some(error) = &self.san_error {
+                    <div class="c-error">{ error }</div>
+                }
+                <input
+                    class="c-fen-input"
+                    placeholder="Paste a FEN to load it"
+                    onchange={ fen_onchange }
+                />
+                if let Some(error) = &self.fen_error {
+                    <div class="c-error">{ error }</div>
+                }
             </div>
         }
     }
 }
 
 impl App {
-    fn make_board_html() -> Html {
-        let mut board = Vec::with_capacity(BOARD_SIZE);
-        for row in 0..BOARD_SIZE {
-            let mut board_row = Vec::with_capacity(BOARD_SIZE);
-            for col in 0..BOARD_SIZE {
-                let tile_color = if (row + col) % 2 == 0 {
-                    "c-tile-white"
+    /// Replaces the game with `game`, forgetting everything about
+    /// the old one.
+    fn set_game(&mut self, game: Game) {
+        self.game = game;
+        self.computer_timeout = None;
+        self.premove = None;
+        self.fen_error = None;
+        self.san_error = None;
+        self.viewing = None;
+        self.selected = None;
+        self.hovered = None;
+        self.highlights.clear();
+        self.arrows.clear();
+    }
+
+    /// Whether the board is shown from black's side right now.
+    /// Turning by itself follows the current position of the game,
+    /// not an earlier one being looked at, so going through the
+    /// game keeps the board still.
+    fn is_flipped(&self) -> bool {
+        if self.auto_flip {
+            self.game.turn() == Color::Black
+        } else {
+            self.flipped
+        }
+    }
+
+    /// The position shown on the board: an earlier one being
+    /// looked at, or else the current one.
+    fn displayed(&self) -> &Game {
+        self.viewing.as_ref().unwrap_or(&self.game)
+    }
+
+    /// Shows the position after the first `ply` half moves of
+    /// the game. Returns whether anything changed.
+    fn view_ply(&mut self, ply: usize) -> bool {
+        self.selected = None;
+        self.hovered = None;
+        if ply == self.game.ply() {
+            return self.viewing.take().is_some();
+        }
+        let mut game = self.game.clone();
+        if game.undo_to(ply).is_err() {
+            return false;
+        }
+        self.viewing = Some(game);
+        true
+    }
+
+    /// Gets the computer thinking about its move if it is its
+    /// turn and it is not already.
+    fn start_computer(&mut self, ctx: &Context<Self>) {
+        if self.computer != Some(self.game.turn())
+            || self.computer_timeout.is_some()
+            || self.game.status() != GameStatus::Ongoing
+        {
+            return;
+        }
+        let link = ctx.link().clone();
+        self.computer_timeout = Some(Timeout::new(COMPUTER_DELAY, move || {
+            link.send_message(Msg::ComputerMove)
+        }));
+    }
+
+    /// The legal moves of the piece on the tile with index
+    /// `index`, or none once the game is over. While it is the
+    /// computer's turn these are the premoves the player could
+    /// queue, as if the computer passed.
+    fn movable(&self, index: usize) -> Vec<Move> {
+        if self.game.status() != GameStatus::Ongoing {
+            return vec![];
+        }
+        if self.computer != Some(self.game.turn()) {
+            return self.game.legal_moves_for(index);
+        }
+        // Passing is not allowed in check, so no premoves then
+        let mut next = self.game.clone();
+        match next.make_null_move() {
+            Ok(()) => next.legal_moves_for(index),
+            Err(_) => vec![],
+        }
+    }
+
+    /// Plays a move chosen on the board, or queues it as the
+    /// premove while it is the computer's turn.
+    fn play(&mut self, m: Move) {
+        if self.computer == Some(self.game.turn()) {
+            self.premove = Some(m);
+        } else {
+            self.game.make_move(m).unwrap();
+        }
+    }
+
+    /// The legal move of the selected piece to the tile with
+    /// index `to`, if there is one. The board has no way to pick
+    /// a piece to promote to, so clicking or dragging a pawn to
+    /// the last rank always promotes it to a queen.
+    fn selected_move(&self, to: usize) -> Option<Move> {
+        let (_, moves) = self.selected.as_ref()?;
+        moves
+            .iter()
+            .copied()
+            .find(|m| m.to() == to && matches!(m.promotion(), None | Some(PieceType::Queen)))
+    }
+
+    /// Copies `text` to the clipboard in the background, then
+    /// reports back whether it worked.
+    fn copy(&self, ctx: &Context<Self>, text: String) {
+        let link = ctx.link().clone();
+        wasm_bindgen_futures::spawn_local(async move {
+            if write_clipboard(&text).await {
+                link.send_message(Msg::Copied);
+            } else {
+                link.send_message(Msg::CopyFailed(text));
+            }
+        });
+    }
+
+    /// Draws the arrows as an SVG laid over the board, in units
+    /// of one tile.
+    fn make_arrows_html(&self) -> Html {
+        let arrows = self.arrows.iter().map(|&(from, to)| {
+            let center = |index: usize| {
+                let index = if self.is_flipped() {
+                    BOARD_SIZE * BOARD_SIZE - 1 - index
                 } else {
-                    "c-tile-black"
+                    index
                 };
-                let board_tile = html! {
-                    <div class={ classes!("c-tile", tile_color) }></div>
-                };
-                board_row.push(board_tile);
-            }
-            let board_row = html! {
-                <div class="c-row">{ for board_row }</div>
+                let (x, y) = (index % BOARD_SIZE, index / BOARD_SIZE);
+                (x as f64 + 0.5, y as f64 + 0.5)
             };
-            board.push(board_row);
+            let ((x1, y1), (x2, y2)) = (center(from), center(to));
+            // Stop short of the middle of the tile so the arrowhead
+            // ends there
+            let length = ((x2 - x1).powi(2) + (y2 - y1).powi(2)).sqrt();
+            let shorten = 0.3 / length;
+            let (x2, y2) = (x2 - (x2 - x1) * shorten, y2 - (y2 - y1) * shorten);
+            html! {
+                <line
+                    x1={ x1.to_string() }
+                    y1={ y1.to_string() }
+                    x2={ x2.to_string() }
+                    y2={ y2.to_string() }
+                    class="c-arrow"
+                    marker-end="url(#c-arrowhead)"
+                />
+            }
+        });
+        html! {
+            <svg class="c-arrows" viewBox="0 0 8 8">
+                <defs>
+                    <marker
+                        id="c-arrowhead"
+                        viewBox="0 0 4 4"
+                        refX="2"
+                        refY="2"
+                        markerWidth="4"
+                        markerHeight="4"
+                        orient="auto"
+                    >
+                        <path d="M 0 0 L 4 2 L 0 4 z" class="c-arrowhead" />
+                    </marker>
+                </defs>
+                { for arrows }
+            </svg>
         }
+    }
+
+    /// Announces the result once the game is over, with a button
+    /// to start a new one. It is hidden while looking back at an
+    /// earlier position, so the game can still be gone through.
+    fn make_game_over_html(&self, ctx: &Context<Self>) -> Html {
+        let status = self.game.status();
+        if status == GameStatus::Ongoing || self.viewing.is_some() {
+            return html! {};
+        }
+        let title = match status {
+            GameStatus::Checkmate(_) => "Checkmate",
+            GameStatus::Stalemate => "Stalemate",
+            GameStatus::InsufficientMaterial => "Insufficient material",
+            GameStatus::DeadPosition => "Dead position",
+            GameStatus::FivefoldRepetition => "Fivefold repetition",
+            GameStatus::SeventyFiveMoveRule => "Seventy-five move rule",
+            GameStatus::DrawClaimed(_) => "Draw claimed",
+            GameStatus::Ongoing => unreachable!(),
+        };
+        let result = match status.result() {
+            GameResult::WhiteWins => "White wins",
+            GameResult::BlackWins => "Black wins",
+            GameResult::Draw | GameResult::Ongoing => "Draw",
+        };
+        let onreset = ctx.link().callback(|_| Msg::Reset);
         html! {
-            <div
-                class="c-board"
-            >
-                { for board }
+            <div class="c-game-over">
+                <h2>{ format!("{} \u{2014} {}", title, result) }</h2>
+                if let Some(reason) = self.game.status_reason() {
+                    <p>{ reason }</p>
+                }
+                <button onclick={ onreset }>{ "New Game" }</button>
             </div>
         }
     }
+
+    /// What to mark on the board for the position shown.
+    fn highlights(&self) -> Highlights {
+        let targets = |moves: &Option<(usize, Vec<Move>)>| -> Vec<usize> {
+            match moves {
+                Some((_, moves)) => moves.iter().map(|m| m.to()).collect(),
+                None => vec![],
+            }
+        };
+        let game = self.displayed();
+        let selected_targets = targets(&self.selected);
+        Highlights {
+            last_move: game.last_move().map(|m| (m.from(), m.to())),
+            selected: self.selected.as_ref().map(|(from, _)| *from),
+            drop: self
+                .drag_over
+                .map(|over| (over, selected_targets.contains(&over))),
+            targets: selected_targets,
+            hover_targets: targets(&self.hovered),
+            marked: self.highlights.clone(),
+            premove: self
+                .premove
+                .filter(|_| self.viewing.is_none())
+                .map(|m| (m.from(), m.to())),
+            // The player moves their pieces even during the computer's
+            // turn, as premoves
+            draggable: self.viewing.is_none().then(|| match self.computer {
+                Some(computer) => computer.opposite(),
+                None => game.turn(),
+            }),
+        }
+    }
+}
+
+/// Everything marked on the board besides the pieces, by tile
+/// index.
+#[derive(Default)]
+struct Highlights {
+    /// The tiles the last move went from and to.
+    last_move: Option<(usize, usize)>,
+    /// The tile with the piece picked up to be moved.
+    selected: Option<usize>,
+    /// Where the selected piece can move to.
+    targets: Vec<usize>,
+    /// Where the piece under the mouse can move to.
+    hover_targets: Vec<usize>,
+    /// The tile a piece is being dragged over, and whether it
+    /// can be dropped there.
+    drop: Option<(usize, bool)>,
+    /// The tiles highlighted with a right click.
+    marked: Vec<usize>,
+    /// The tiles the queued premove goes from and to.
+    premove: Option<(usize, usize)>,
+    /// The color whose pieces can be picked up, if any.
+    draggable: Option<Color>,
+}
+
+/// Draws a board in the colors of `theme`, with the pieces drawn
+/// from `pieces`, and what is marked on it. With `flipped` set
+/// a1 is in the top right, as black sees it. `coords` picks a
+/// label shown in the corner of each tile. Each tile is also
+/// labelled with what is on it for screen readers.
+fn render_board(
+    board: &Board,
+    theme: Theme,
+    flipped: bool,
+    pieces: PieceSet,
+    coords: Coords,
+    highlights: &Highlights,
+) -> Html {
+    let mut rows = Vec::with_capacity(BOARD_SIZE);
+    for row in 0..BOARD_SIZE {
+        let mut tiles = Vec::with_capacity(BOARD_SIZE);
+        for col in 0..BOARD_SIZE {
+            let (x, y) = if flipped {
+                (BOARD_SIZE - 1 - col, BOARD_SIZE - 1 - row)
+            } else {
+                (col, row)
+            };
+            let index = y * BOARD_SIZE + x;
+            let square_color = Board::square_color(x, y);
+            // The plain class is kept for the highlights, which color
+            // the tile the same in every theme
+            let tile_color = match square_color {
+                Color::White => "c-tile-white",
+                Color::Black => "c-tile-black",
+            };
+            let piece = board.get(x, y);
+            let last = highlights
+                .last_move
+                .is_some_and(|(from, to)| from == index || to == index);
+            let premove = highlights
+                .premove
+                .is_some_and(|(from, to)| from == index || to == index);
+            let drop_class = match highlights.drop {
+                Some((over, true)) if over == index => Some("c-drop-legal"),
+                Some((over, false)) if over == index => Some("c-drop-illegal"),
+                _ => None,
+            };
+            let tile_class = classes!(
+                "c-tile",
+                tile_color,
+                theme.tile_class(square_color),
+                last.then_some("c-last-move"),
+                premove.then_some("c-premove"),
+                (highlights.selected == Some(index)).then_some("c-selected"),
+                highlights.targets.contains(&index).then_some("c-target"),
+                highlights
+                    .hover_targets
+                    .contains(&index)
+                    .then_some("c-hover-target"),
+                highlights.marked.contains(&index).then_some("c-highlight"),
+                drop_class,
+            );
+            let draggable = piece.is_some_and(|piece| Some(piece.color()) == highlights.draggable);
+            let square = format!("{}{}", (b'a' + x as u8) as char, BOARD_SIZE - y);
+            let label = match piece {
+                Some(piece) => format!(
+                    "{} {} on {}",
+                    piece.color().name(),
+                    piece.piece_type().name(),
+                    square
+                ),
+                None => format!("empty {}", square),
+            };
+            let coord = match coords {
+                Coords::Hidden => None,
+                Coords::Squares => Some(square.clone()),
+                Coords::Indices => Some(index.to_string()),
+            };
+            let symbol = piece.map(|piece| pieces.symbol(piece));
+            tiles.push(html! {
+                <div
+                    class={ tile_class }
+                    draggable={ draggable.to_string() }
+                    role="img"
+                    aria-label={ label }
+                >
+                    if let Some(coord) = coord {
+                        <span class="c-coord" aria-hidden="true">{ coord }</span>
+                    }
+                    { for symbol }
+                </div>
+            });
+        }
+        rows.push(html! {
+            <div class="c-row">{ for tiles }</div>
+        });
+    }
+    html! {
+        <div class="c-board">{ for rows }</div>
+    }
+}
+
+/// The index of the tile under the mouse on a board shown the
+/// way `flipped` says, or `None` if it is off the board.
+fn tile_at(board: &NodeRef, e: &MouseEvent, flipped: bool) -> Option<usize> {
+    let rect = board.cast::<HtmlElement>()?.get_bounding_client_rect();
+    let x = (e.client_x() as f64 - rect.left()) / rect.width() * BOARD_SIZE as f64;
+    let y = (e.client_y() as f64 - rect.top()) / rect.height() * BOARD_SIZE as f64;
+    let on_board = 0.0..BOARD_SIZE as f64;
+    if !on_board.contains(&x) || !on_board.contains(&y) {
+        return None;
+    }
+    let index = y as usize * BOARD_SIZE + x as usize;
+    Some(if flipped {
+        BOARD_SIZE * BOARD_SIZE - 1 - index
+    } else {
+        index
+    })
+}
+
+/// Sends the messages for keyboard shortcuts pressed anywhere on
+/// the page: `f` flips the board, `r` starts a new game, `u` or
+/// Backspace takes back a move, the left and right arrows go
+/// through the game, and `c` labels the tiles. Keys typed into a
+/// text box, or pressed with Ctrl, Alt, or Meta, are left alone.
+fn listen_for_shortcuts(ctx: &Context<App>) -> EventListener {
+    let link = ctx.link().clone();
+    EventListener::new(&gloo_utils::document(), "keydown", move |e| {
+        let e = match e.dyn_ref::<KeyboardEvent>() {
+            Some(e) => e,
+            None => return,
+        };
+        let typing = e.target().is_some_and(|target| {
+            target.has_type::<HtmlInputElement>()
+                || target.has_type::<HtmlTextAreaElement>()
+                || target.has_type::<HtmlSelectElement>()
+        });
+        if typing || e.ctrl_key() || e.alt_key() || e.meta_key() {
+            return;
+        }
+        let msg = match e.key().as_str() {
+            "c" => Msg::ToggleCoords,
+            "f" => Msg::Flip,
+            "r" => Msg::Reset,
+            "u" | "Backspace" => Msg::Undo,
+            "ArrowLeft" => Msg::Back,
+            "ArrowRight" => Msg::Forward,
+            _ => return,
+        };
+        // Keep the arrows from scrolling the page
+        e.prevent_default();
+        link.send_message(msg);
+    })
+}
+
+/// Plays the computer's reply, then the premove queued while it was
+/// thinking. The reply may have made the premove illegal, in which
+/// case it is dropped. Returns whether the premove was played.
+fn play_reply(game: &mut Game, reply: Option<Move>, premove: Option<Move>) -> bool {
+    if let Some(m) = reply {
+        game.make_move(m).unwrap();
+    }
+    premove.is_some_and(|m| game.make_move(m).is_ok())
+}
+
+/// Removes `item` from `items` if it is there, or else adds it.
+fn toggle<T: PartialEq>(items: &mut Vec<T>, item: T) {
+    match items.iter().position(|other| *other == item) {
+        Some(index) => {
+            items.remove(index);
+        }
+        None => items.push(item),
+    }
+}
+
+/// Writes text to the clipboard, returning whether it worked.
+/// Browsers without the clipboard API, or that refuse permission,
+/// give `false`.
+async fn write_clipboard(text: &str) -> bool {
+    let navigator = gloo_utils::window().navigator();
+    let clipboard = match Reflect::get(&navigator, &JsValue::from_str("clipboard")) {
+        Ok(clipboard) if !clipboard.is_undefined() => clipboard,
+        _ => return false,
+    };
+    let promise = Reflect::get(&clipboard, &JsValue::from_str("writeText"))
+        .and_then(|write| write.dyn_into::<Function>())
+        .and_then(|write| write.call1(&clipboard, &JsValue::from_str(text)))
+        .and_then(|promise| promise.dyn_into::<Promise>());
+    match promise {
+        Ok(promise) => JsFuture::from(promise).await.is_ok(),
+        Err(_) => false,
+    }
 }
 
 fn main() {
     yew::start_app::<App>();
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tile_classes_follow_the_theme() {
+        assert_eq!(Theme::Grey.tile_class(Color::White), "c-grey-tile-white");
+        assert_eq!(Theme::Green.tile_class(Color::Black), "c-green-tile-black");
+        for name in ["grey", "green", "brown", "blue"] {
+            let theme = Theme::from_name(name).unwrap();
+            assert_eq!(
+                theme.tile_class(Color::White),
+                format!("c-{}-tile-white", name)
+            );
+            assert_eq!(
+                theme.tile_class(Color::Black),
+                format!("c-{}-tile-black", name)
+            );
+        }
+        assert!(Theme::from_name("purple").is_none());
+    }
+
+    #[test]
+    fn piece_sets_draw_symbols_or_letters() {
+        let board = Board::from_fen(STARTING_FEN).unwrap();
+        let king = board.get(4, 7).unwrap();
+        assert_eq!(PieceSet::Symbols.symbol(king), "♔");
+        assert_eq!(PieceSet::Letters.symbol(king), "K");
+    }
+
+    #[test]
+    fn toggling_adds_then_removes() {
+        let mut arrows = vec![(12, 28)];
+        toggle(&mut arrows, (52, 36));
+        assert_eq!(arrows, [(12, 28), (52, 36)]);
+        toggle(&mut arrows, (12, 28));
+        assert_eq!(arrows, [(52, 36)]);
+        toggle(&mut arrows, (52, 36));
+        assert!(arrows.is_empty());
+    }
+
+    #[test]
+    fn coords_cycle_back_to_hidden() {
+        let mut coords = Coords::Hidden;
+        for expected in [Coords::Squares, Coords::Indices, Coords::Hidden] {
+            coords = coords.next();
+            assert!(coords == expected);
+        }
+    }
+
+    #[test]
+    fn premoves_are_played_only_if_still_legal() {
+        let uci = |text: &str| Move::from_uci(text).ok();
+        let mut game = Game::from_moves(&["e4"]).unwrap();
+        assert!(play_reply(&mut game, uci("e7e5"), uci("g1f3")));
+        assert_eq!(game.ply(), 3);
+
+        // Taking the pawn on d5 is no longer possible once it has moved on
+        let mut game = Game::from_moves(&["e4", "d5"]).unwrap();
+        game.make_move_uci("b1c3").unwrap();
+        assert!(!play_reply(&mut game, uci("d5d4"), uci("e4d5")));
+        assert_eq!(game.ply(), 4);
+        assert_eq!(game.turn(), Color::White);
+
+        let mut game = Game::from_moves(&["e4"]).unwrap();
+        assert!(!play_reply(&mut game, uci("e7e5"), None));
+        assert_eq!(game.ply(), 2);
+    }
+
+    /// The text inside a rendered node, ignoring the markup.
+    fn text(node: &Html) -> String {
+        match node {
+            Html::VTag(tag) => tag.children().iter().map(text).collect(),
+            Html::VList(list) => list.iter().map(text).collect(),
+            Html::VText(vtext) => vtext.text.to_string(),
+            _ => String::new(),
+        }
+    }
+
+    /// The label and text of every tile in a rendered board, in the
+    /// order they are drawn.
+    fn tiles(node: &Html, found: &mut Vec<(String, String)>) {
+        match node {
+            Html::VTag(tag) => match tag.attributes.iter().find(|&(key, _)| key == "aria-label") {
+                Some((_, label)) => found.push((label.to_owned(), text(node))),
+                None => tag.children().iter().for_each(|child| tiles(child, found)),
+            },
+            Html::VList(list) => list.iter().for_each(|child| tiles(child, found)),
+            _ => {}
+        }
+    }
+
+    #[test]
+    fn custom_boards_show_their_pieces() {
+        let board = Board::from_fen("8/8/8/3k4/8/8/8/4K2R").unwrap();
+        let html = render_board(
+            &board,
+            Theme::Grey,
+            false,
+            PieceSet::Letters,
+            Coords::Hidden,
+            &Highlights::default(),
+        );
+        let mut found = vec![];
+        tiles(&html, &mut found);
+        assert_eq!(found.len(), 64);
+        assert_eq!(found[0], ("empty a8".to_owned(), String::new()));
+        assert_eq!(found[27], ("black king on d5".to_owned(), "k".to_owned()));
+        assert_eq!(found[60], ("white king on e1".to_owned(), "K".to_owned()));
+        assert_eq!(found[63], ("white rook on h1".to_owned(), "R".to_owned()));
+        let pieces: String = found.iter().map(|(_, text)| text.as_str()).collect();
+        assert_eq!(pieces, "kKR");
+
+        // Flipped, h1 is drawn first
+        let html = render_board(
+            &board,
+            Theme::Grey,
+            true,
+            PieceSet::Letters,
+            Coords::Hidden,
+            &Highlights::default(),
+        );
+        let mut found = vec![];
+        tiles(&html, &mut found);
+        assert_eq!(found[0].0, "white rook on h1");
+    }
+}
@@ -0,0 +1,28 @@
+use chess_rs::game::{GameStatus, STARTING_FEN};
+use chess_rs::{Board, Color, Game, Move};
+
+#[test]
+fn game_can_be_created_and_queried() {
+    let mut game = Game::from_fen(STARTING_FEN).unwrap();
+    assert_eq!(game.turn(), Color::White);
+    assert_eq!(game.legal_moves().len(), 20);
+    assert_eq!(game.status(), GameStatus::Ongoing);
+
+    game.make_move(Move::from_uci("e2e4").unwrap()).unwrap();
+    game.make_move(Move::from_uci("e7e5").unwrap()).unwrap();
+    assert_eq!(game.turn(), Color::White);
+    assert_eq!(
+        game.to_fen(),
+        "rnbqkbnr/pppp1ppp/8/4p3/4P3/8/PPPP1PPP/RNBQKBNR w KQkq e6 0 2"
+    );
+
+    assert!(game.board().get(4, 4).is_some());
+    assert!(game.make_move(Move::from_uci("e4e5").unwrap()).is_err());
+}
+
+#[test]
+fn board_can_be_read_on_its_own() {
+    let board = Board::from_fen("4k3/8/8/8/8/8/8/4K2R").unwrap();
+    assert!(board.get(7, 7).is_some());
+    assert!(board.get(0, 0).is_none());
+}